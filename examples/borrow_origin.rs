@@ -0,0 +1,33 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use vec_cell::VecCell;
+
+fn main() {
+    let v = VecCell::from_iter([1, 2]);
+    assert!(v.borrowed_at(0).is_none());
+
+    let line = line!() + 1;
+    let a = v.get_mut(0).unwrap();
+    let location = v.borrowed_at(0).unwrap();
+    assert_eq!(location.file(), file!());
+    assert_eq!(location.line(), line);
+
+    // Conflicting borrows report where the element was borrowed.
+    let err = panic::catch_unwind(AssertUnwindSafe(|| drop(v.borrow(0)))).unwrap_err();
+    let message = err.downcast_ref::<String>().unwrap();
+    assert_eq!(
+        *message,
+        format!(
+            "already mutably borrowed: index 0, borrowed at {}:{line}:15",
+            file!()
+        )
+    );
+
+    // Released elements have no origin.
+    drop(a);
+    assert!(v.borrowed_at(0).is_none());
+
+    let line = line!() + 1;
+    let _iter = v.try_iter().unwrap();
+    assert_eq!(v.borrowed_at(1).unwrap().line(), line);
+}
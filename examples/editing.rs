@@ -0,0 +1,146 @@
+use vec_cell::VecCell;
+
+fn main() {
+    let mut v = VecCell::from_iter([1, 3]);
+    v.reserve_exact(8);
+    assert_eq!(v.capacity(), 10);
+    v.reserve(4);
+    assert_eq!(v.capacity(), 10);
+
+    // Editing takes `&mut self`, so no element can be borrowed meanwhile.
+    v.insert(1, 2);
+    v.insert(3, 4);
+    assert_eq!(v.len(), 4);
+    assert!(v.try_iter().unwrap().eq(&[1, 2, 3, 4]));
+    assert!(v.get_mut(3).is_ok());
+    v.debug_validate();
+
+    // Flags stay aligned with their elements through mixed edits.
+    v.push(6);
+    v.insert(0, 0);
+    assert_eq!(v.pop(), Some(6));
+    v.insert(5, 5);
+    v.insert(2, 9);
+    assert!(v.try_iter().unwrap().eq(&[0, 1, 9, 2, 3, 4, 5]));
+    assert!((0..v.len()).all(|i| v.get_mut(i).is_ok()));
+    assert_eq!(v.remove(0), 0);
+    assert_eq!(v.remove(1), 9);
+    assert_eq!(v.pop(), Some(5));
+    v.debug_validate();
+
+    assert_eq!(v.remove(0), 1);
+    assert_eq!(v.remove(2), 4);
+    assert_eq!(v.len(), 2);
+    // Shifted elements are borrowed at their new indices.
+    assert_eq!(*v.get_mut(1).unwrap(), 3);
+    assert!(v.get_mut(2).is_err());
+    assert!(v.try_iter().unwrap().eq(&[2, 3]));
+    v.debug_validate();
+
+    v.push(4);
+    v.push(5);
+    assert_eq!(v.swap_remove(0), 2);
+    assert!(v.try_iter().unwrap().eq(&[5, 3, 4]));
+    v.debug_validate();
+
+    // Removing the last or the only element moves nothing.
+    v.push(6);
+    assert_eq!(v.swap_remove(3), 6);
+    let mut single = VecCell::from([1]);
+    assert_eq!(single.swap_remove(0), 1);
+    assert!(single.is_empty());
+    single.debug_validate();
+    // The moved element keeps working at its new index.
+    v.push(6);
+    assert_eq!(v.swap_remove(1), 3);
+    assert!(v.try_iter().unwrap().eq(&[5, 6, 4]));
+    assert_eq!(*v.get_mut(1).unwrap(), 6);
+    assert!(v.get_mut(3).is_err());
+    v.set(1, 3).unwrap();
+    v.debug_validate();
+
+    // Removing an element with a leaked borrow doesn't leave the rest blocked.
+    std::mem::forget(v.get_mut(0).unwrap());
+    assert_eq!(v.remove(0), 5);
+    assert!(v.try_iter().is_ok());
+    v.debug_validate();
+
+    v.push(6);
+    std::mem::forget(v.get(2).unwrap());
+    v.truncate(5);
+    assert_eq!(v.len(), 3);
+    v.truncate(2);
+    assert!(v.try_iter().unwrap().eq(&[3, 4]));
+    v.debug_validate();
+
+    // Growing keeps leaked borrows, shrinking forgets them.
+    v.resize(4, 0);
+    std::mem::forget(v.get_mut(3).unwrap());
+    let mut next = 0;
+    v.resize_with(6, || {
+        next += 1;
+        next
+    });
+    assert!(v.try_iter().is_err());
+    v.resize(2, 0);
+    assert!(v.try_iter().unwrap().eq(&[3, 4]));
+    v.debug_validate();
+
+    v.extend(5..10);
+    std::mem::forget(v.get(3).unwrap());
+    v.retain(|x| x % 2 == 1);
+    assert!(v.try_iter().unwrap().eq(&[3, 5, 7, 9]));
+    v.debug_validate();
+
+    v.push(11);
+    std::mem::forget(v.get(2).unwrap());
+    let mut drain = v.drain(1..3);
+    assert_eq!(drain.next(), Some(5));
+    drop(drain);
+    assert!(v.try_iter().unwrap().eq(&[3, 9, 11]));
+    assert!(v.drain(..).eq([3, 9, 11]));
+    assert!(v.is_empty());
+    v.debug_validate();
+    // Leaked borrows after the range are released as the elements move.
+    v.extend(1..6);
+    std::mem::forget(v.get_mut(4).unwrap());
+    assert!(v.drain(0..1).eq([1]));
+    assert!(v.get_mut(3).is_ok());
+    v.debug_validate();
+    // Leaking the iterator leaks the rest of the elements too.
+    std::mem::forget(v.get(3).unwrap());
+    let mut drain = v.drain(1..2);
+    assert_eq!(drain.next_back(), Some(3));
+    std::mem::forget(drain);
+    assert_eq!(v.len(), 1);
+    assert!(v.get(1).is_err());
+    v.debug_validate();
+    v.clear();
+    v.push(1);
+
+    v.reserve(16);
+    v.shrink_to(4);
+    assert!(v.capacity() >= 4 && v.capacity() < 16);
+    v.shrink_to_fit();
+    assert_eq!(v.capacity(), 1);
+    assert_eq!(v.len(), 1);
+
+    // Clearing drops leaked borrows along with the elements.
+    let capacity = v.capacity();
+    std::mem::forget(v.get(0).unwrap());
+    v.clear();
+    assert!(v.is_empty());
+    assert_eq!(v.capacity(), capacity);
+    v.debug_validate();
+
+    // Conversions to and from `Vec` keep the allocation.
+    let elems = Vec::with_capacity(8);
+    let mut v = VecCell::from(elems);
+    v.push(1);
+    assert_eq!(v.capacity(), 8);
+    let elems: Vec<i32> = v.into();
+    assert_eq!(elems, [1]);
+    assert_eq!(elems.capacity(), 8);
+    assert_eq!(VecCell::from(elems).into_vec(), [1]);
+    assert_eq!(VecCell::from([1, 2]).into_inner(), [1, 2]);
+}
@@ -0,0 +1,81 @@
+use vec_cell::{Error, VecCell};
+
+fn main() {
+    let v = VecCell::from_iter([1, 2, 3, 4]);
+
+    let even = v.try_find(|x| x % 2 == 0).unwrap().unwrap();
+    assert_eq!(*even, 2);
+    assert!(v.is_borrowed(1));
+    assert!(!v.is_borrowed(0));
+    assert!(v.try_find(|&x| x > 4).unwrap().is_none());
+
+    // Mutably borrowed elements can only be skipped by finding a match first.
+    let c = v.get_mut(2).unwrap();
+    assert_eq!(*v.try_find(|&x| x == 1).unwrap().unwrap(), 1);
+    assert_eq!(v.try_find(|&x| x == 4).unwrap_err(), Error::Aliasing);
+    assert_eq!(v.position(|&x| x == 2), Ok(Some(1)));
+    assert_eq!(v.position(|&x| x == 4).unwrap_err(), Error::Aliasing);
+    drop(c);
+    drop(even);
+    // Only an index is returned, so the element can be borrowed mutably right away.
+    let index = v.position(|&x| x == 4).unwrap().unwrap();
+    *v.get_mut(index).unwrap() = 5;
+    assert_eq!(v.position(|&x| x == 4), Ok(None));
+    v.set(index, 4).unwrap();
+    v.debug_validate();
+
+    // Claiming a free slot marks it mutably borrowed right away.
+    let slots = VecCell::from_iter([Some(1), None, None]);
+    let mut slot = slots.try_find_mut(Option::is_none).unwrap().unwrap();
+    *slot = Some(2);
+    assert!(slots.is_borrowed_mut(1));
+    assert_eq!(
+        slots.try_find_mut(Option::is_none).unwrap_err(),
+        Error::Aliasing
+    );
+    drop(slot);
+    let slot = slots.try_find_mut(Option::is_none).unwrap().unwrap();
+    assert!(slots.is_borrowed_mut(2));
+    drop(slot);
+
+    let first = slots.get(0).unwrap();
+    assert_eq!(
+        slots.try_find_mut(Option::is_some).unwrap_err(),
+        Error::Aliasing
+    );
+    drop(first);
+    assert!(slots.try_find_mut(|&x| x == Some(5)).unwrap().is_none());
+    slots.debug_validate();
+
+    // Binary search over sorted elements.
+    let sorted = VecCell::from_iter([(1, 'a'), (3, 'b'), (5, 'c'), (7, 'd')]);
+    assert_eq!(sorted.binary_search(&(5, 'c')), Ok(Ok(2)));
+    assert_eq!(sorted.binary_search_by_key(&3, |&(k, _)| k), Ok(Ok(1)));
+    assert_eq!(sorted.binary_search_by_key(&4, |&(k, _)| k), Ok(Err(2)));
+    assert_eq!(sorted.binary_search_by_key(&9, |&(k, _)| k), Ok(Err(4)));
+    assert_eq!(VecCell::<i32>::new().binary_search(&1), Ok(Err(0)));
+
+    // Only the probed elements have to be readable.
+    let last = sorted.get_mut(3).unwrap();
+    assert_eq!(sorted.binary_search_by_key(&3, |&(k, _)| k), Ok(Ok(1)));
+    assert_eq!(
+        sorted.binary_search_by_key(&7, |&(k, _)| k),
+        Err(Error::Aliasing)
+    );
+    drop(last);
+
+    // Membership checks short-circuit and accept borrowed forms of the elements.
+    let names = VecCell::from_iter([String::from("a"), String::from("b")]);
+    let b = names.get_mut(1).unwrap();
+    assert_eq!(names.contains("a"), Ok(true));
+    assert_eq!(names.contains("c"), Err(Error::Aliasing));
+    drop(b);
+    assert_eq!(names.contains("c"), Ok(false));
+    assert_eq!(names.contains(&String::from("b")), Ok(true));
+    assert!(!names.is_borrowed(0));
+
+    // Shared borrows don't get in the way.
+    let _a = v.get(0).unwrap();
+    assert_eq!(v.contains(&1), Ok(true));
+    assert_eq!(v.contains(&0), Ok(false));
+}
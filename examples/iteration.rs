@@ -1,19 +1,104 @@
-use vec_cell::VecCell;
+use vec_cell::{Error, VecCell};
 
 fn main() {
     let mut v = VecCell::from_iter([1, 2]);
 
-    // `&VecCell` doesn't implement IntoIterator, so `for e in &v {}` does not compile.
-    // You have to use `VecCell::try_iter`.
+    // `for e in &v {}` yields `Ref` guards and panics if an element is mutably borrowed.
+    // `VecCell::try_iter` and `VecCell::iter_refs` report that as an error instead.
+    let mut sum = 0;
+    for e in &v {
+        assert!(v.get_mut(1).is_err());
+        sum += *e;
+    }
+    assert_eq!(sum, 3);
     for _ in v.try_iter().unwrap() {}
 
+    // Elements stay borrowed while the iterator is alive.
+    let iter = v.try_iter().unwrap();
+    assert!(v.get_mut(0).is_err());
+    drop(iter);
+
     let a = v.get_mut(0).unwrap();
 
     // Cannot immutably iterate while we have borrowed elements.
     assert!(v.try_iter().is_err());
+    assert!(v.try_as_slice().is_err());
+    drop(a);
+
+    // The whole collection can be borrowed as a slice, too.
+    // Any number of them may be alive, alongside element borrows.
+    let slice = v.try_as_slice().unwrap();
+    let other = v.try_as_slice().unwrap();
+    assert_eq!(slice[1], 2);
+    assert!(other.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(*v.get(1).unwrap(), 2);
+    assert!(v.get_mut(1).is_err());
+    drop(slice);
+    assert!(v.get_mut(1).is_err());
+    drop(other);
+
+    // Guards can outlive the iterator, keeping their elements borrowed.
+    let refs: Vec<_> = v.iter_refs().unwrap().collect();
+    assert_eq!(*refs[1], 2);
+    assert!(v.get_mut(0).is_err());
+    let mut iter = v.iter_refs().unwrap();
+    let last = iter.next_back().unwrap();
+    drop((iter, refs));
+    assert!(v.get_mut(0).is_ok());
+    assert!(v.get_mut(1).is_err());
+    assert!(v.iter_refs().is_ok());
+    drop(last);
+
+    // Mutable iteration through `&VecCell`, one guard per element.
+    for mut e in v.try_iter_mut().unwrap() {
+        *e *= 10;
+    }
+    let mut iter = v.try_iter_mut().unwrap();
+    assert_eq!(v.get(1).unwrap_err(), Error::Aliasing);
+    let mut first = iter.next().unwrap();
+    // Yielded guards cover distinct elements, so they can be kept alive together.
+    let last = iter.next_back().unwrap();
+    *first += *last;
+    drop(iter);
+    assert!(v.try_iter_mut().is_err());
+    drop((first, last));
+    let shared = v.get(0).unwrap();
+    assert_eq!(v.try_iter_mut().unwrap_err(), Error::Aliasing);
+    drop(shared);
+    assert!(v.try_iter().unwrap().eq(&[30, 20]));
+    v.debug_validate();
+
+    // Exclusive access to the whole slice through `&VecCell`.
+    let mut slice = v.try_as_mut_slice().unwrap();
+    slice.rotate_left(1);
+    assert!(v.try_iter().is_err());
+    assert_eq!(v.get(0).unwrap_err(), Error::Aliasing);
+    assert!(v.try_as_mut_slice().is_err());
+    drop(slice);
+    let shared = v.get(1).unwrap();
+    assert_eq!(v.try_as_mut_slice().unwrap_err(), Error::Aliasing);
+    drop(shared);
+    v.try_as_mut_slice().unwrap().rotate_left(1);
+    assert!(v.try_iter().unwrap().eq(&[30, 20]));
 
     // Note: For other kind of iteration borrow checker will ensure we do not have any borrows.
-    drop(a);
+    v.as_mut_slice().sort_unstable_by(|a, b| b.cmp(a));
+    assert_eq!(v.as_mut_slice(), [30, 20]);
+    assert_eq!(v.as_slice().iter().max(), Some(&30));
+    // Flags are left as they were.
+    assert!(v.get_mut(0).is_ok() && v.get_mut(1).is_ok());
+    v.debug_validate();
+    v.for_each_mut(|e| *e += 1);
+    let borrowed = v.get(0).unwrap();
+    assert!(v.try_for_each_mut(|_| unreachable!()).is_err());
+    drop(borrowed);
+    v.try_for_each_mut(|e| *e -= 1).unwrap();
+    assert!(v.try_iter().unwrap().eq(&[30, 20]));
+    for chunk in v.chunks_mut(1) {
+        chunk[0] /= 10;
+    }
+    assert_eq!(v.chunks_mut(3).count(), 1);
+    assert_eq!(v.as_slice(), [3, 2]);
     for _ in &mut v {}
     for _ in v {}
 }
@@ -0,0 +1,64 @@
+use vec_cell::{Error, VecCell};
+
+fn main() {
+    let v = VecCell::from_iter([1, 2, 3, 4]);
+
+    let [mut a, b, c] = v.get_many_mut([3, 0, 2]).unwrap();
+    *a += *b + *c;
+    assert_eq!(*a, 8);
+    drop((a, b, c));
+
+    assert_eq!(v.get_many_mut([0, 4]).unwrap_err(), Error::OutOfBounds);
+    assert_eq!(v.get_many_mut([1, 1]).unwrap_err(), Error::Aliasing);
+
+    // Failing on the last index leaves no flags set on the others.
+    let borrowed = v.get(2).unwrap();
+    assert_eq!(v.get_many_mut([0, 1, 2]).unwrap_err(), Error::Aliasing);
+    assert!(!v.is_borrowed(0));
+    assert!(!v.is_borrowed(1));
+    v.debug_validate();
+    drop(borrowed);
+    assert!(v.get_many_mut([0, 1, 2]).is_ok());
+
+    let (mut a, b) = v.get_two_mut(0, 1).unwrap();
+    *a = 10;
+    assert_eq!(*a + *b, 12);
+    assert_eq!(v.get_two_mut(1, 2).unwrap_err(), Error::Aliasing);
+    drop((a, b));
+    assert_eq!(v.get_two_mut(2, 2).unwrap_err(), Error::DuplicateIndex);
+    assert_eq!(v.get_two_mut(2, 4).unwrap_err(), Error::OutOfBounds);
+    *v.get_mut(0).unwrap() = 1;
+
+    // Every element at once, each guard released on its own.
+    let mut refs = v.each_ref().unwrap();
+    assert_eq!(refs.iter().map(|r| **r).sum::<i32>(), 14);
+    let last = refs.pop().unwrap();
+    drop(refs);
+    assert!(v.get_mut(0).is_ok());
+    assert_eq!(v.get_mut(3).unwrap_err(), Error::Aliasing);
+    drop(last);
+
+    let held = v.get_mut(3).unwrap();
+    assert_eq!(v.each_ref().unwrap_err(), Error::Aliasing);
+    assert!(!v.is_borrowed(0));
+    drop(held);
+    v.debug_validate();
+
+    // Each task gets exclusive access to its own element.
+    let mut tasks = v.each_mut().unwrap();
+    for (i, task) in tasks.iter_mut().enumerate() {
+        **task = i as i32;
+    }
+    let first = tasks.remove(0);
+    drop(tasks);
+    assert!((1..4).all(|i| v.get_mut(i).is_ok()));
+    assert_eq!(v.get_mut(0).unwrap_err(), Error::Aliasing);
+    assert_eq!(v.each_mut().unwrap_err(), Error::Aliasing);
+    assert!(!v.is_borrowed(1));
+    drop(first);
+    let shared = v.get(2).unwrap();
+    assert_eq!(v.each_mut().unwrap_err(), Error::Aliasing);
+    drop(shared);
+    assert!(v.try_iter().unwrap().eq(&[0, 1, 2, 3]));
+    v.debug_validate();
+}
@@ -0,0 +1,69 @@
+use vec_cell::{BorrowState, Error, Ref, RefMut, VecCell};
+
+#[derive(Debug)]
+struct Entity {
+    name: String,
+    hp: u32,
+}
+
+fn main() {
+    let v = VecCell::from_iter([
+        Entity {
+            name: String::from("a"),
+            hp: 10,
+        },
+        Entity {
+            name: String::from("b"),
+            hp: 20,
+        },
+    ]);
+
+    // Projections keep the whole element borrowed.
+    let name = Ref::map(v.get(0).unwrap(), |e| e.name.as_str());
+    assert_eq!(&*name, "a");
+    assert_eq!(v.borrow_state(0), Ok(BorrowState::Immutable(1)));
+    assert_eq!(v.get_mut(0).unwrap_err(), Error::Aliasing);
+    let hp = Ref::map(v.get(0).unwrap(), |e| &e.hp);
+    assert_eq!(*hp, 10);
+    assert_eq!(v.borrow_state(0), Ok(BorrowState::Immutable(2)));
+
+    // And release it exactly once.
+    drop(name);
+    assert_eq!(v.borrow_state(0), Ok(BorrowState::Immutable(1)));
+    drop(hp);
+    assert!(v.get_mut(0).is_ok());
+    assert_eq!(v.get(1).unwrap().hp, 20);
+    v.debug_validate();
+
+    // Mutable projections keep the whole element mutably borrowed.
+    let mut hp = RefMut::map(v.get_mut(1).unwrap(), |e| &mut e.hp);
+    *hp -= 5;
+    assert!(v.is_borrowed_mut(1));
+    assert_eq!(v.get(1).unwrap_err(), Error::Aliasing);
+    drop(hp);
+    assert_eq!(v.get(1).unwrap().hp, 15);
+    v.debug_validate();
+
+    // Split guards release the element once both are gone.
+    let (mut name, mut hp) = RefMut::map_split(v.get_mut(0).unwrap(), |e| (&mut e.name, &mut e.hp));
+    name.push('!');
+    *hp += 1;
+    drop(name);
+    assert!(v.is_borrowed_mut(0));
+    assert_eq!(v.get(0).unwrap_err(), Error::Aliasing);
+    drop(hp);
+    assert!(!v.is_borrowed(0));
+    assert_eq!(v.get(0).unwrap().name, "a!");
+    v.debug_validate();
+
+    // Clones share the immutable borrow and release it one by one.
+    let a = v.get(1).unwrap();
+    let b = Ref::clone(&a);
+    assert_eq!(v.borrow_state(1), Ok(BorrowState::Immutable(2)));
+    assert_eq!(b.hp, 15);
+    drop(a);
+    assert_eq!(v.get_mut(1).unwrap_err(), Error::Aliasing);
+    drop(b);
+    assert!(v.get_mut(1).is_ok());
+    v.debug_validate();
+}
@@ -0,0 +1,130 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::Mutex,
+};
+
+use vec_cell::{Error, VecCell};
+
+/// Runs `f`, returning the message it panicked with.
+fn panic_message(f: impl FnOnce()) -> String {
+    let err = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+    *err.downcast::<String>().unwrap()
+}
+
+static PANIC_FILE: Mutex<String> = Mutex::new(String::new());
+
+fn main() {
+    // Remember where the expected panics happen instead of printing them.
+    panic::set_hook(Box::new(|info| {
+        *PANIC_FILE.lock().unwrap() = info.location().unwrap().file().to_owned();
+    }));
+
+    let v = VecCell::from_iter([1, 2, 3]);
+
+    assert_eq!(
+        panic_message(|| drop(v.borrow(3))),
+        "index out of bounds: the len is 3 but the index is 3"
+    );
+    // Panics point at the caller, not into the library.
+    assert_eq!(*PANIC_FILE.lock().unwrap(), file!());
+    assert_eq!(
+        panic_message(|| drop(v.borrow_mut(12))),
+        "index out of bounds: the len is 3 but the index is 12"
+    );
+
+    let mut w = VecCell::from([1]);
+    assert_eq!(
+        panic_message(|| w.insert(2, 0)),
+        "insertion index (is 2) should be <= len (is 1)"
+    );
+    assert_eq!(*PANIC_FILE.lock().unwrap(), file!());
+    assert_eq!(
+        panic_message(|| {
+            w.remove(1);
+        }),
+        "removal index (is 1) should be < len (is 1)"
+    );
+    assert_eq!(*PANIC_FILE.lock().unwrap(), file!());
+    assert_eq!(
+        panic_message(|| {
+            w.swap_remove(1);
+        }),
+        "swap_remove index (is 1) should be < len (is 1)"
+    );
+    assert_eq!(*PANIC_FILE.lock().unwrap(), file!());
+    let chunks = panic::catch_unwind(AssertUnwindSafe(|| drop(w.chunks_mut(0))));
+    assert!(chunks.is_err());
+    assert_eq!(*PANIC_FILE.lock().unwrap(), file!());
+
+    let a = v.borrow_mut(0);
+    assert!(panic_message(|| drop(v.borrow(0))).starts_with("already mutably borrowed: index 0"));
+    assert!(
+        panic_message(|| drop(v.borrow_mut(0))).starts_with("already mutably borrowed: index 0")
+    );
+    assert_eq!(
+        panic_message(|| for _ in &v {}),
+        "cannot iterate VecCell: Borrow would lead to illegal aliasing"
+    );
+    assert_eq!(*PANIC_FILE.lock().unwrap(), file!());
+    drop(a);
+
+    let b = v.borrow(1);
+    assert!(panic_message(|| drop(v.borrow_mut(1))).starts_with("already borrowed: index 1"));
+    drop(b);
+
+    // An element panicking on drop can't leave flags for elements that are gone.
+    let mut v = VecCell::from_iter([PanicOnDrop(false), PanicOnDrop(true), PanicOnDrop(false)]);
+    assert_eq!(panic_message(|| v.clear()), "dropped");
+    assert!(v.is_empty());
+    v.debug_validate();
+    v.extend([PanicOnDrop(false), PanicOnDrop(true), PanicOnDrop(false)]);
+    assert_eq!(panic_message(|| v.truncate(1)), "dropped");
+    assert_eq!(v.get(1).unwrap_err(), Error::OutOfBounds);
+    v.debug_validate();
+    v.push(PanicOnDrop(true));
+    assert_eq!(panic_message(|| v.resize_with(1, || unreachable!())), "dropped");
+    assert_eq!(v.len(), 1);
+    v.debug_validate();
+    // A panic while growing keeps the elements pushed so far.
+    let mut calls = 0;
+    let resize = || {
+        v.resize_with(4, || {
+            calls += 1;
+            assert!(calls < 3, "{}", "no more elements");
+            PanicOnDrop(false)
+        })
+    };
+    assert_eq!(panic_message(resize), "no more elements");
+    assert_eq!(v.len(), 3);
+    assert!(v.try_as_slice().is_ok());
+    v.debug_validate();
+    v.truncate(1);
+    v.extend([PanicOnDrop(true), PanicOnDrop(false), PanicOnDrop(false)]);
+    // Flags move along with the kept elements.
+    std::mem::forget(v.get_mut(2).unwrap());
+    std::mem::forget(v.get(3).unwrap());
+    let mut calls = 0;
+    let retain = || {
+        v.retain(|_| {
+            calls += 1;
+            calls % 2 == 1
+        })
+    };
+    assert_eq!(panic_message(retain), "dropped");
+    assert_eq!(v.len(), 2);
+    assert_eq!(v.get(1).unwrap_err(), Error::Aliasing);
+    assert_eq!(v.get(2).unwrap_err(), Error::OutOfBounds);
+    v.debug_validate();
+    v.reset_borrows();
+}
+
+#[derive(Debug)]
+struct PanicOnDrop(bool);
+
+impl Drop for PanicOnDrop {
+    fn drop(&mut self) {
+        if self.0 {
+            panic!("{}", "dropped");
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use vec_cell::{Error, VecCell};
+
+fn main() {
+    let v = VecCell::from_iter([1, 2]);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut a = v.get_mut(0).unwrap();
+        let _b = v.get(1).unwrap();
+        *a += 1;
+        panic!("update failed halfway");
+    }));
+    assert!(result.is_err());
+
+    // Only the mutably borrowed element is poisoned.
+    assert!(v.is_poisoned(0));
+    assert!(!v.is_poisoned(1));
+    assert_eq!(v.get(0).unwrap_err(), Error::Poisoned);
+    assert_eq!(v.get_mut(0).unwrap_err(), Error::Poisoned);
+    assert_eq!(v.try_iter().unwrap_err(), Error::Poisoned);
+    assert!(v.get_mut(1).is_ok());
+
+    // The borrow itself was released, clearing the poison makes the element usable again.
+    v.debug_validate();
+    v.clear_poison(0).unwrap();
+    assert!(!v.is_poisoned(0));
+    assert_eq!(*v.get(0).unwrap(), 2);
+    assert_eq!(v.clear_poison(2), Err(Error::OutOfBounds));
+
+    // Only elements handed out by an iterator are poisoned.
+    let v = VecCell::from_iter([1, 2, 3]);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut iter = v.try_iter_mut().unwrap();
+        *iter.next().unwrap() += 1;
+        panic!("update failed halfway");
+    }));
+    assert!(result.is_err());
+    assert_eq!([0, 1, 2].map(|i| v.is_poisoned(i)), [false, false, false]);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut iter = v.try_iter_mut().unwrap();
+        let _first = iter.next().unwrap();
+        panic!("update failed halfway");
+    }));
+    assert!(result.is_err());
+    assert_eq!([0, 1, 2].map(|i| v.is_poisoned(i)), [true, false, false]);
+    v.clear_poison(0).unwrap();
+
+    // A borrow taken and released cleanly while already unwinding doesn't poison.
+    struct UpdateOnDrop<'a>(&'a VecCell<i32>);
+    impl Drop for UpdateOnDrop<'_> {
+        fn drop(&mut self) {
+            *self.0.get_mut(1).unwrap() += 1;
+        }
+    }
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _update = UpdateOnDrop(&v);
+        panic!("unwinding");
+    }));
+    assert!(result.is_err());
+    assert!(!v.is_poisoned(1));
+    assert_eq!(*v.get(1).unwrap(), 3);
+
+    // A predicate only sees `&T`, so its panic can't leave the element half-updated.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| v.pop_if(|_| panic!("no decision"))));
+    assert!(result.is_err());
+    assert!(!v.is_poisoned(2));
+    assert_eq!(v.pop_if(|&x| x == 3), Ok(Some(3)));
+    v.debug_validate();
+}
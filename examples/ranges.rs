@@ -0,0 +1,136 @@
+use vec_cell::{Error, VecCell};
+
+fn main() {
+    let v = VecCell::from_iter(0..8);
+
+    // Each worker gets its own segment.
+    let mut segments = v.get_disjoint_ranges_mut(&[0..3, 5..8, 3..5]).unwrap();
+    for segment in &mut segments {
+        segment.reverse();
+    }
+    assert_eq!(*segments[0], [2, 1, 0]);
+    assert_eq!(*segments[2], [4, 3]);
+
+    // Segments are released one by one.
+    let second = segments.remove(1);
+    drop(segments);
+    assert!(v.get_mut(0).is_ok());
+    assert_eq!(v.get(7).unwrap_err(), Error::Aliasing);
+    drop(second);
+    assert!(v.try_iter().unwrap().eq(&[2, 1, 0, 4, 3, 7, 6, 5]));
+
+    assert_eq!(
+        v.get_disjoint_ranges_mut(&[0..4, 3..5]).unwrap_err(),
+        Error::Aliasing
+    );
+    assert_eq!(
+        v.get_disjoint_ranges_mut(&[0..1, 6..9]).unwrap_err(),
+        Error::OutOfBounds
+    );
+
+    assert!(v.get_disjoint_ranges_mut(&[2..2, 1..3]).is_ok());
+
+    // Nothing is borrowed when any of the ranges can't be.
+    let borrowed = v.get(6).unwrap();
+    assert_eq!(
+        v.get_disjoint_ranges_mut(&[0..2, 4..7]).unwrap_err(),
+        Error::Aliasing
+    );
+    assert!(!v.is_borrowed(0));
+    v.debug_validate();
+    drop(borrowed);
+
+    // Shared windows may overlap each other, but not mutable borrows.
+    let window = v.get_range(1..=3).unwrap();
+    assert_eq!(*window, [1, 0, 4]);
+    let all = v.get_range(..).unwrap();
+    assert_eq!(all.len(), 8);
+    assert_eq!(*v.get_range(6..).unwrap(), [6, 5]);
+    assert_eq!(v.get_mut(2).unwrap_err(), Error::Aliasing);
+    assert!(v.get(2).is_ok());
+    drop((window, all));
+
+    let c = v.get_mut(2).unwrap();
+    assert_eq!(v.get_range(..3).unwrap_err(), Error::Aliasing);
+    assert_eq!(v.get_range(3..9).unwrap_err(), Error::OutOfBounds);
+    assert!(v.get_range(3..3).unwrap().is_empty());
+    drop(c);
+    v.debug_validate();
+
+    // Mutable windows can be sorted in place, side by side.
+    let mut low = v.get_range_mut(..4).unwrap();
+    let mut high = v.get_range_mut(4..).unwrap();
+    low.sort();
+    high.copy_within(1.., 0);
+    assert_eq!(v.get(3).unwrap_err(), Error::Aliasing);
+    assert_eq!(v.get_range_mut(3..=4).unwrap_err(), Error::Aliasing);
+    drop(low);
+    assert!(v.get(3).is_ok());
+    assert_eq!(v.get(4).unwrap_err(), Error::Aliasing);
+    drop(high);
+    assert!(v.try_iter().unwrap().eq(&[0, 1, 2, 4, 7, 6, 5, 5]));
+    v.debug_validate();
+
+    // `get` and `get_mut` take ranges as well, like `slice::get`.
+    let v = VecCell::from([0, 1, 2, 3]);
+    assert_eq!(*v.get(1..3).unwrap(), [1, 2]);
+    assert_eq!(*v.get(1..=3).unwrap(), [1, 2, 3]);
+    assert_eq!(*v.get(..).unwrap(), [0, 1, 2, 3]);
+    assert_eq!(*v.get(..2).unwrap(), [0, 1]);
+    assert_eq!(*v.get(..=0).unwrap(), [0]);
+    assert_eq!(*v.get(3..).unwrap(), [3]);
+    assert_eq!(*v.get(2).unwrap(), 2);
+    v.get_mut(..2).unwrap().swap(0, 1);
+    v.get_mut(2..=3).unwrap().reverse();
+    assert_eq!(*v.get(..).unwrap(), [1, 0, 3, 2]);
+
+    // Empty ranges borrow nothing, even next to mutable borrows.
+    let all = v.get_mut(..).unwrap();
+    assert!(v.get(0..0).unwrap().is_empty());
+    assert!(v.get_mut(4..).unwrap().is_empty());
+    assert!(v.get(..0).unwrap().is_empty());
+    assert_eq!(v.get(..=0).unwrap_err(), Error::Aliasing);
+    drop(all);
+
+    // Bounds are checked like slice indexing, except that nothing panics.
+    assert_eq!(v.get(2..5).unwrap_err(), Error::OutOfBounds);
+    assert_eq!(v.get(5..).unwrap_err(), Error::OutOfBounds);
+    assert_eq!(v.get_mut(..=4).unwrap_err(), Error::OutOfBounds);
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = v.get(3..1);
+    assert_eq!(reversed.unwrap_err(), Error::OutOfBounds);
+    assert_eq!(v.get(..=usize::MAX).unwrap_err(), Error::OutOfBounds);
+    assert_eq!(v.get(4).unwrap_err(), Error::OutOfBounds);
+    v.debug_validate();
+
+    // Copies within the collection may overlap in either direction.
+    let v = VecCell::from([1, 2, 3, 4, 5]);
+    v.copy_within(1..4, 2).unwrap();
+    assert_eq!(*v.get(..).unwrap(), [1, 2, 2, 3, 4]);
+    v.copy_within(2.., 0).unwrap();
+    assert_eq!(*v.get(..).unwrap(), [2, 3, 4, 3, 4]);
+    // Disjoint source elements only need to be readable.
+    let source = v.get(0).unwrap();
+    v.copy_within(..1, 4).unwrap();
+    assert_eq!(v.copy_within(1..3, 0).unwrap_err(), Error::Aliasing);
+    assert_eq!(v.copy_within(1..2, 0).unwrap_err(), Error::Aliasing);
+    drop(source);
+    let dest = v.get(3).unwrap();
+    assert_eq!(v.copy_within(..2, 2).unwrap_err(), Error::Aliasing);
+    drop(dest);
+    assert_eq!(*v.get(..).unwrap(), [2, 3, 4, 3, 2]);
+    assert_eq!(v.copy_within(3.., 4).unwrap_err(), Error::OutOfBounds);
+    assert_eq!(v.copy_within(..6, 0).unwrap_err(), Error::OutOfBounds);
+
+    let names = VecCell::from_iter(["a", "b", "c"].map(String::from));
+    names.clone_within(..2, 1).unwrap();
+    assert!(names.try_iter().unwrap().eq(["a", "a", "b"].iter()));
+    names.clone_within(1.., 0).unwrap();
+    assert!(names.try_iter().unwrap().eq(["a", "b", "b"].iter()));
+    names.clone_within(2.., 0).unwrap();
+    assert!(names.try_iter().unwrap().eq(["b", "b", "b"].iter()));
+    let _borrowed = names.get_mut(2).unwrap();
+    assert_eq!(names.clone_within(..1, 2).unwrap_err(), Error::Aliasing);
+    v.debug_validate();
+    names.debug_validate();
+}
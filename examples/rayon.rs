@@ -0,0 +1,34 @@
+use rayon::prelude::*;
+use vec_cell::{BorrowState, Error, VecCell};
+
+fn main() {
+    let mut v: VecCell<u64> = (1..=10_000).collect();
+
+    // `&mut self` rules out any guards.
+    v.par_iter_mut().for_each(|x| *x *= 2);
+    (&mut v).into_par_iter().for_each(|x| *x += 1);
+    assert_eq!(v.par_try_iter().unwrap().sum::<u64>(), 10_000 * 10_001 + 10_000);
+
+    // Elements stay immutably borrowed while the iterator lives.
+    let a = v.get(0).unwrap();
+    let iter = v.par_try_iter().unwrap();
+    assert_eq!(v.borrow_state(0), Ok(BorrowState::Immutable(2)));
+    assert_eq!(v.get_mut(1).unwrap_err(), Error::Aliasing);
+    assert_eq!(iter.filter(|&&x| x % 2 == 0).count(), 0);
+    assert_eq!(v.borrow_state(0), Ok(BorrowState::Immutable(1)));
+    drop(a);
+
+    let b = v.get_mut(3).unwrap();
+    assert_eq!(v.par_try_iter().err(), Some(Error::Aliasing));
+    drop(b);
+
+    // Dropped on another thread, the iterator leaks its borrow instead of racing on the
+    // counters.
+    let iter = v.par_try_iter().unwrap();
+    std::thread::scope(|s| {
+        s.spawn(move || assert_eq!(iter.len(), 10_000));
+    });
+    assert!(v.get_mut(0).is_err());
+    assert_eq!(v.reset_borrows(), 10_000);
+    v.debug_validate();
+}
@@ -0,0 +1,32 @@
+use vec_cell::VecCell;
+
+fn main() {
+    let v = VecCell::from_iter([1, 2, 3]);
+    let json = serde_json::to_string(&v).unwrap();
+    // Same format as a `Vec`.
+    assert_eq!(json, serde_json::to_string(&vec![1, 2, 3]).unwrap());
+    assert_eq!(json, "[1,2,3]");
+
+    // Immutable borrows don't get in the way.
+    let a = v.get(0).unwrap();
+    assert_eq!(serde_json::to_string(&v).unwrap(), json);
+    drop(a);
+
+    // A mutably borrowed element might be mid-update.
+    let b = v.get_mut(1).unwrap();
+    let err = serde_json::to_string(&v).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "cannot serialize VecCell: Borrow would lead to illegal aliasing"
+    );
+    drop(b);
+
+    let w: VecCell<i32> = serde_json::from_str(&json).unwrap();
+    assert!(w.try_iter().unwrap().eq(&[1, 2, 3]));
+    assert!(w.validate().mismatches.is_empty());
+    assert_eq!(w.validate().unborrowed, 3);
+    let nested: Vec<VecCell<String>> = serde_json::from_str(r#"[["a"],[]]"#).unwrap();
+    assert_eq!(*nested[0].get(0).unwrap(), "a");
+    assert!(nested[1].is_empty());
+    assert!(serde_json::from_str::<VecCell<i32>>("{}").is_err());
+}
@@ -0,0 +1,177 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use vec_cell::{BorrowState, Error, VecCell};
+
+fn hash(v: &impl Hash) -> u64 {
+    let mut state = DefaultHasher::new();
+    v.hash(&mut state);
+    state.finish()
+}
+
+fn main() {
+    let v = VecCell::from_iter([1, 2, 3]);
+
+    // Any number of immutable borrows, of the same or of different elements.
+    let a = v.get(0).unwrap();
+    let b = v.get(1).unwrap();
+    let c = v.get(0).unwrap();
+    assert_eq!(*a + *b + *c, 4);
+    assert_eq!(v.borrow_state(0), Ok(BorrowState::Immutable(2)));
+    assert_eq!(v.borrow_state(2), Ok(BorrowState::None));
+    assert_eq!(v.borrow_state(3), Err(Error::OutOfBounds));
+    assert!(v.is_borrowed(0));
+    assert!(!v.is_borrowed_mut(0));
+    assert!(!v.is_borrowed(2));
+    assert!(!v.is_borrowed(3));
+
+    // Bookkeeping can be checked without borrowing anything.
+    let report = v.validate();
+    assert_eq!(report.immutably_borrowed, 2);
+    assert_eq!(report.unborrowed, 1);
+    assert!(report.mismatches.is_empty());
+    v.debug_validate();
+
+    // Element 0 is released only once all of its borrows are gone.
+    drop(a);
+    assert_eq!(v.get_mut(0).unwrap_err(), Error::Aliasing);
+    drop(c);
+    assert!(v.get_mut(0).is_ok());
+
+    // Dropping borrows of other elements doesn't affect element 1.
+    assert_eq!(v.get_mut(1).unwrap_err(), Error::Aliasing);
+    drop(b);
+    assert!(v.get_mut(1).is_ok());
+
+    // A leaked borrow keeps its element borrowed instead of corrupting the bookkeeping.
+    std::mem::forget(v.get(2).unwrap());
+    assert!(v.get(2).is_ok());
+    assert_eq!(v.get_mut(2).unwrap_err(), Error::Aliasing);
+
+    // With exclusive access leaked borrows can be released.
+    let mut v = v;
+    assert_eq!(v.reset_borrows(), 1);
+    assert!(v.get_mut(2).is_ok());
+
+    // Ends are borrowed through the same bookkeeping.
+    assert_eq!(*v.first().unwrap(), 1);
+    let last = v.get_mut(2).unwrap();
+    assert_eq!(v.last().unwrap_err(), Error::Aliasing);
+    drop(last);
+    assert_eq!(*v.last().unwrap(), 3);
+    let empty = VecCell::<i32>::new();
+    assert_eq!(empty.first().unwrap_err(), Error::OutOfBounds);
+    assert_eq!(empty.last().unwrap_err(), Error::OutOfBounds);
+
+    // Clones can be taken next to other shared borrows.
+    let names = VecCell::from_iter([String::from("a"), String::from("b")]);
+    let a = names.get(0).unwrap();
+    assert_eq!(names.get_cloned(0).unwrap(), *a);
+    let b = names.get_mut(1).unwrap();
+    assert_eq!(names.get_cloned(1).unwrap_err(), Error::Aliasing);
+    drop(b);
+    assert_eq!(names.borrow_state(0), Ok(BorrowState::Immutable(1)));
+    assert_eq!(names.borrow_state(1), Ok(BorrowState::None));
+
+    // Clones start without borrows, but need every element readable.
+    let snapshot = names.clone();
+    assert!(!snapshot.is_borrowed(0));
+    let b = names.get_mut(1).unwrap();
+    assert_eq!(names.try_clone().unwrap_err(), Error::Aliasing);
+    drop(b);
+    assert_eq!(names.try_clone().unwrap(), snapshot);
+
+    // Snapshots into a `Vec` work the same way.
+    let a = names.get(0).unwrap();
+    assert_eq!(names.try_to_vec().unwrap(), ["a", "b"]);
+    let mut buf = Vec::with_capacity(8);
+    names.try_clone_into(&mut buf).unwrap();
+    assert_eq!(buf, ["a", "b"]);
+    assert!(buf.capacity() >= 8);
+    drop(a);
+    let b = names.get_mut(1).unwrap();
+    assert_eq!(names.try_to_vec().unwrap_err(), Error::Aliasing);
+    assert_eq!(names.try_clone_into(&mut buf).unwrap_err(), Error::Aliasing);
+    assert_eq!(buf, ["a", "b"]);
+    drop(b);
+
+    // Mapping works the same way, into a different element type.
+    let lens = names.map(String::len).unwrap();
+    assert_eq!(lens, VecCell::from([1, 1]));
+    assert!(!lens.is_borrowed(0));
+    let b = names.get_mut(1).unwrap();
+    assert_eq!(names.map(String::len).unwrap_err(), Error::Aliasing);
+    drop(b);
+
+    // Comparison ignores shared borrows.
+    let a = snapshot.get(0).unwrap();
+    assert_eq!(names, snapshot);
+    assert_ne!(names, VecCell::from_iter([String::from("a")]));
+    drop(a);
+
+    // Hashing matches the plain `Vec`, so `VecCell`s can be used as keys.
+    let a = names.get(0).unwrap();
+    assert_eq!(hash(&names), hash(&vec!["a", "b"]));
+    drop(a);
+
+    // A queue shared behind `Rc` can be consumed without `&mut`.
+    let queue = std::rc::Rc::new(VecCell::from([1, 2, 3]));
+    let consumer = queue.clone();
+    let first = queue.get(0).unwrap();
+    assert_eq!(consumer.try_pop(), Ok(Some(3)));
+    assert_eq!(*first, 1);
+    let last = queue.get(1).unwrap();
+    assert_eq!(consumer.try_pop().unwrap_err(), Error::Aliasing);
+    assert_eq!(consumer.pop_if(|_| true).unwrap_err(), Error::Aliasing);
+    drop(last);
+    assert_eq!(consumer.pop_if(|&x| x > 2), Ok(None));
+    // Only the element the predicate saw is ever popped.
+    let pushed = consumer.pop_if(|&x| {
+        queue.try_push(99).unwrap();
+        x == 2
+    });
+    assert_eq!(pushed.unwrap_err(), Error::Aliasing);
+    assert_eq!(consumer.try_pop(), Ok(Some(99)));
+    assert_eq!(consumer.pop_if(|&x| x == 2), Ok(Some(2)));
+    assert_eq!(queue.len(), 1);
+    drop(first);
+    assert_eq!(consumer.try_pop(), Ok(Some(1)));
+    assert_eq!(consumer.try_pop(), Ok(None));
+    assert_eq!(consumer.pop_if(|_| unreachable!()), Ok(None));
+    queue.debug_validate();
+
+    // Pushing through `&self` can't move borrowed elements.
+    let nodes = VecCell::with_capacity(2);
+    nodes.try_push(1).unwrap();
+    let first = nodes.get(0).unwrap();
+    nodes.try_push(2).unwrap();
+    assert_eq!(nodes.capacity(), 2);
+    assert_eq!(nodes.try_push(3).unwrap_err(), Error::Aliasing);
+    assert_eq!(*first, 1);
+    drop(first);
+    let mut second = nodes.get_mut(1).unwrap();
+    assert_eq!(nodes.try_push(3).unwrap_err(), Error::Aliasing);
+    *second += 1;
+    drop(second);
+    // Without borrows the collection grows as usual.
+    nodes.try_push(3).unwrap();
+    assert!(nodes.capacity() > 2);
+    assert!(nodes.try_iter().unwrap().eq(&[1, 3, 3]));
+    assert!(nodes.get_mut(2).is_ok());
+    nodes.debug_validate();
+
+    // All of the capacity reported is usable, also when converting from a `Vec`.
+    let nodes = VecCell::from(Vec::with_capacity(8));
+    nodes.try_push(1).unwrap();
+    let first = nodes.get(0).unwrap();
+    assert_eq!(nodes.capacity(), 8);
+    for i in 2..=8 {
+        nodes.try_push(i).unwrap();
+    }
+    assert_eq!(nodes.try_push(9).unwrap_err(), Error::Aliasing);
+    assert_eq!(*first, 1);
+    drop(first);
+    nodes.debug_validate();
+}
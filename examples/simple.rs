@@ -1,7 +1,9 @@
+use std::{panic::AssertUnwindSafe, rc::Rc};
+
 use vec_cell::{Error, VecCell};
 
 fn main() {
-    let v = VecCell::from_iter([1, 2]);
+    let v = VecCell::from([1, 2]);
     let mut a = v.get_mut(0).unwrap();
     let mut b = v.get_mut(1).unwrap();
     *a += 1;
@@ -9,6 +11,43 @@ fn main() {
     assert_eq!(*a, 2);
     assert_eq!(*b, 3);
 
+    // Guards format like the element itself.
+    assert_eq!(format!("{a:?}"), "2");
+    let v2 = VecCell::from_iter([(1, 2)]);
+    let c = v2.get(0).unwrap();
+    assert_eq!(format!("{c:?}"), "(1, 2)");
+    assert_eq!(format!("{c:#?}"), format!("{:#?}", (1, 2)));
+
     // already borrowed
+    assert!(v.is_borrowed_mut(0));
     assert_eq!(v.get_mut(0).unwrap_err(), Error::Aliasing);
+
+    // Panicking counterparts, like `RefCell::borrow`/`RefCell::borrow_mut`.
+    drop((a, b));
+    *v.borrow_mut(0) += 1;
+    assert_eq!(*v.borrow(0), 3);
+    let _b = v.borrow(1);
+    let err = std::panic::catch_unwind(AssertUnwindSafe(|| drop(v.borrow_mut(1)))).unwrap_err();
+    assert!(err
+        .downcast_ref::<String>()
+        .unwrap()
+        .starts_with("already borrowed: index 1"));
+
+    // The ends of a stack can be mutated in place.
+    let mut stack = VecCell::from_iter([1, 2]);
+    *stack.last_mut().unwrap() += 10;
+    *stack.first_mut().unwrap() += 20;
+    assert!(stack.try_iter().unwrap().eq(&[21, 12]));
+    stack.clear();
+    assert_eq!(stack.first_mut().unwrap_err(), Error::OutOfBounds);
+    assert_eq!(stack.last_mut().unwrap_err(), Error::OutOfBounds);
+
+    // With a single element both ends are the same element.
+    stack.push(1);
+    let stack = Rc::new(stack);
+    let top = stack.last_mut().unwrap();
+    assert_eq!(stack.last_mut().unwrap_err(), Error::Aliasing);
+    assert_eq!(stack.first_mut().unwrap_err(), Error::Aliasing);
+    drop(top);
+    assert!(stack.first_mut().is_ok());
 }
@@ -0,0 +1,79 @@
+use vec_cell::{BorrowState, Error, VecCell};
+
+fn main() {
+    let v = VecCell::from_iter([1, 2, 3]);
+
+    let (first, rest) = v.split_first().unwrap();
+    assert_eq!(*first, 1);
+    assert_eq!(*rest, [2, 3]);
+    assert_eq!(v.borrow_state(0), Ok(BorrowState::Immutable(1)));
+    assert_eq!(v.borrow_state(2), Ok(BorrowState::Immutable(1)));
+
+    // Head and tail are released independently.
+    drop(rest);
+    assert!(v.get_mut(2).is_ok());
+    assert_eq!(v.get_mut(0).unwrap_err(), Error::Aliasing);
+    drop(first);
+
+    let (mut last, mut rest) = v.split_last_mut().unwrap();
+    *last += rest.iter().sum::<i32>();
+    rest[0] = 0;
+    assert!(v.is_borrowed_mut(0) && v.is_borrowed_mut(2));
+    assert_eq!(v.split_first().unwrap_err(), Error::Aliasing);
+    drop(rest);
+    // Nothing is left borrowed when the split fails halfway.
+    assert_eq!(v.split_first_mut().unwrap_err(), Error::Aliasing);
+    assert!(!v.is_borrowed(0));
+    drop(last);
+    assert!(v.try_iter().unwrap().eq(&[0, 2, 6]));
+
+    // A single element has an empty rest.
+    let single = VecCell::from_iter([1]);
+    let (first, rest) = single.split_first_mut().unwrap();
+    assert_eq!(*first, 1);
+    assert!(rest.is_empty());
+    drop((first, rest));
+    assert_eq!(single.split_last().unwrap().1.len(), 0);
+
+    // Halves at `mid` are released in any order.
+    let (left, right) = v.split_at(1).unwrap();
+    assert_eq!((&*left, &*right), (&[0][..], &[2, 6][..]));
+    drop(left);
+    assert_eq!(v.borrow_state(0), Ok(BorrowState::None));
+    assert_eq!(v.get_mut(1).unwrap_err(), Error::Aliasing);
+    drop(right);
+    let (left, right) = v.split_at(3).unwrap();
+    assert_eq!((left.len(), right.len()), (3, 0));
+    drop((right, left));
+    assert_eq!(v.split_at(4).unwrap_err(), Error::OutOfBounds);
+
+    // Both halves are written at once and released in reverse order.
+    let (mut left, mut right) = v.split_at_mut(2).unwrap();
+    left.fill(1);
+    right[0] = 7;
+    assert_eq!(v.get(0).unwrap_err(), Error::Aliasing);
+    drop(right);
+    assert!(v.get_mut(2).is_ok());
+    drop(left);
+    assert!((0..3).all(|i| v.get_mut(i).is_ok()));
+    assert!(v.try_iter().unwrap().eq(&[1, 1, 7]));
+
+    // A borrow in either half fails the whole split.
+    let held = v.get(2).unwrap();
+    assert_eq!(v.split_at_mut(1).unwrap_err(), Error::Aliasing);
+    assert!(!v.is_borrowed(0));
+    drop(held);
+
+    // Exclusive access splits the plain slice instead.
+    let mut owned = VecCell::from([1, 2, 3, 4]);
+    let (front, back) = owned.as_mut_slice().split_at_mut(2);
+    front.swap_with_slice(back);
+    assert!(owned.try_iter().unwrap().eq(&[3, 4, 1, 2]));
+    owned.debug_validate();
+
+    let empty = VecCell::<i32>::new();
+    assert_eq!(empty.split_first().unwrap_err(), Error::OutOfBounds);
+    assert_eq!(empty.split_last_mut().unwrap_err(), Error::OutOfBounds);
+    v.debug_validate();
+    single.debug_validate();
+}
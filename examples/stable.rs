@@ -0,0 +1,47 @@
+use vec_cell::{BorrowState, Error, StableVecCell};
+
+fn main() {
+    let v = StableVecCell::new();
+    v.push(String::from("first"));
+    v.push(String::from("second"));
+
+    // Guards to early elements stay valid while thousands of elements are pushed.
+    let first = v.get(0).unwrap();
+    let mut second = v.get_mut(1).unwrap();
+    for i in 2..10_000 {
+        v.push(i.to_string());
+    }
+    second.push_str(" and more");
+    assert_eq!(*first, "first");
+    assert_eq!(v.len(), 10_000);
+
+    // Borrows are tracked per element, like in `VecCell`.
+    assert_eq!(v.get_mut(0).unwrap_err(), Error::Aliasing);
+    assert_eq!(v.get(1).unwrap_err(), Error::Aliasing);
+    assert_eq!(v.borrow_state(0), Ok(BorrowState::Immutable(1)));
+    assert_eq!(v.get(10_000).unwrap_err(), Error::OutOfBounds);
+    let mut guards = Vec::new();
+    for i in (2..10_000).step_by(97) {
+        guards.push(v.get_mut(i).unwrap());
+        v.push(format!("pushed while {i} is borrowed"));
+    }
+    for guard in &mut guards {
+        guard.push('!');
+    }
+    drop((first, second, guards));
+    assert_eq!(*v.get(1).unwrap(), "second and more");
+    assert_eq!(*v.get(99).unwrap(), "99!");
+    assert_eq!(*v.get(100).unwrap(), "100");
+
+    let elems = v.into_vec();
+    assert_eq!(elems.len(), 10_000 + (2..10_000).step_by(97).len());
+    assert_eq!(elems[0], "first");
+    assert_eq!(elems.last().unwrap(), "pushed while 9993 is borrowed");
+
+    // Elements left in the cell are dropped with it.
+    let counted = std::rc::Rc::new(());
+    let v: StableVecCell<_> = (0..100).map(|_| counted.clone()).collect();
+    assert_eq!(std::rc::Rc::strong_count(&counted), 101);
+    drop(v);
+    assert_eq!(std::rc::Rc::strong_count(&counted), 1);
+}
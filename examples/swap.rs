@@ -0,0 +1,134 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+};
+
+use vec_cell::{Error, VecCell};
+
+fn main() {
+    // Only shared access is needed.
+    let v = Rc::new(VecCell::from_iter([1, 2, 3]));
+    v.swap(0, 2).unwrap();
+    assert!(v.try_iter().unwrap().eq(&[3, 2, 1]));
+
+    // Borrowed elements stay where they are.
+    let a = v.get(0).unwrap();
+    assert_eq!(v.swap(0, 1).unwrap_err(), Error::Aliasing);
+    assert_eq!(v.swap(1, 0).unwrap_err(), Error::Aliasing);
+    assert_eq!(*a, 3);
+    assert_eq!(*v.get(1).unwrap(), 2);
+    drop(a);
+
+    let b = v.get_mut(1).unwrap();
+    assert_eq!(v.swap(2, 1).unwrap_err(), Error::Aliasing);
+    drop(b);
+    assert!(v.try_iter().unwrap().eq(&[3, 2, 1]));
+
+    // Swapping an element with itself is a no-op, even while borrowed.
+    let _a = v.get(0).unwrap();
+    assert!(v.swap(0, 0).is_ok());
+    assert_eq!(v.swap(3, 3).unwrap_err(), Error::OutOfBounds);
+    assert_eq!(v.swap(0, 3).unwrap_err(), Error::OutOfBounds);
+
+    // Whole contents are exchanged with a scratch buffer, all or nothing.
+    let mut scratch = vec![7, 8, 9];
+    assert_eq!(
+        v.swap_with_slice(&mut scratch[..2]).unwrap_err(),
+        Error::OutOfBounds
+    );
+    assert_eq!(
+        v.swap_with_slice(&mut scratch).unwrap_err(),
+        Error::Aliasing
+    );
+    assert_eq!(scratch, [7, 8, 9]);
+    let back = VecCell::from([1, 2, 3]);
+    back.swap_with_slice(&mut scratch).unwrap();
+    assert_eq!(scratch, [1, 2, 3]);
+    assert!(back.try_iter().unwrap().eq(&[7, 8, 9]));
+    back.copy_from_slice(&[4, 5, 6]).unwrap();
+    assert!(back.try_iter().unwrap().eq(&[4, 5, 6]));
+    let held = back.get(1).unwrap();
+    assert_eq!(back.copy_from_slice(&[0; 3]).unwrap_err(), Error::Aliasing);
+    drop(held);
+    assert_eq!(
+        back.copy_from_slice(&[0; 4]).unwrap_err(),
+        Error::OutOfBounds
+    );
+    assert!(back.try_iter().unwrap().eq(&[4, 5, 6]));
+    let mut back = back;
+    back.as_mut_slice().copy_from_slice(&[1, 2, 3]);
+    back.as_mut_slice().clone_from_slice(&[3, 2, 1]);
+    assert!(back.try_iter().unwrap().eq(&[3, 2, 1]));
+    let mismatch = panic::catch_unwind(AssertUnwindSafe(|| {
+        back.as_mut_slice().copy_from_slice(&[0]);
+    }));
+    assert!(mismatch.is_err());
+    back.debug_validate();
+
+    // Replacing works while other elements are borrowed.
+    let b = v.get_mut(1).unwrap();
+    assert_eq!(v.replace(2, 4), Ok(1));
+    assert!(!v.is_borrowed(2));
+    assert_eq!(v.replace(1, 5).unwrap_err(), Error::Aliasing);
+    assert_eq!(v.replace(0, 5).unwrap_err(), Error::Aliasing);
+    assert_eq!(v.replace(3, 5).unwrap_err(), Error::OutOfBounds);
+    drop(b);
+    assert!(v.try_iter().unwrap().eq(&[3, 2, 4]));
+
+    // The new value can be computed from the old one.
+    assert_eq!(v.replace_with(1, |x| *x * 10), Ok(2));
+    assert_eq!(*v.get(1).unwrap(), 20);
+
+    // The element can't be reached again from the closure.
+    let inner = v.replace_with(1, |x| {
+        assert_eq!(v.get(1).unwrap_err(), Error::Aliasing);
+        *x + 1
+    });
+    assert_eq!(inner, Ok(20));
+
+    // A panicking closure leaves the element untouched and releases it.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        v.replace_with(1, |_| panic!("no new value"))
+    }));
+    assert!(result.is_err());
+    assert!(!v.is_borrowed(1));
+    #[cfg(feature = "poison")]
+    v.clear_poison(1).unwrap();
+    assert_eq!(*v.get(1).unwrap(), 21);
+    v.debug_validate();
+
+    // Taking leaves the default value behind.
+    let jobs = VecCell::from_iter([Some("a"), Some("b")]);
+    let b = jobs.get(1).unwrap();
+    assert_eq!(jobs.take(0), Ok(Some("a")));
+    assert_eq!(*jobs.get(0).unwrap(), None);
+    assert_eq!(jobs.take(1).unwrap_err(), Error::Aliasing);
+    assert_eq!(jobs.take(2).unwrap_err(), Error::OutOfBounds);
+    assert_eq!(*b, Some("b"));
+
+    // Cloning from a slice reuses the elements' allocations.
+    let lines = VecCell::from([String::with_capacity(16), String::new()]);
+    lines.clone_from_slice(&["a".into(), "b".into()]).unwrap();
+    assert!(lines.try_iter().unwrap().eq(["a", "b"].iter()));
+    assert!(lines.get(0).unwrap().capacity() >= 16);
+    assert_eq!(lines.clone_from_slice(&[]).unwrap_err(), Error::OutOfBounds);
+    let held = lines.get_mut(0).unwrap();
+    assert_eq!(
+        lines
+            .clone_from_slice(&["c".into(), "d".into()])
+            .unwrap_err(),
+        Error::Aliasing
+    );
+    drop(held);
+    assert!(lines.try_iter().unwrap().eq(["a", "b"].iter()));
+
+    // Setting drops the old value in place.
+    let names = VecCell::from_iter([String::from("a"), String::from("b")]);
+    let a = names.get_mut(0).unwrap();
+    names.set(1, String::from("c")).unwrap();
+    assert_eq!(names.set(0, String::new()).unwrap_err(), Error::Aliasing);
+    assert_eq!(names.set(2, String::new()).unwrap_err(), Error::OutOfBounds);
+    assert_eq!(*a, "a");
+    assert_eq!(*names.get(1).unwrap(), "c");
+    assert!(!names.is_borrowed(1));
+}
@@ -0,0 +1,21 @@
+use vec_cell::VecCell;
+
+fn main() {
+    let v = VecCell::from_iter([1, 2, 3, 4]);
+
+    // Even and odd indices are disjoint, so every access below is exclusive.
+    for i in (0..v.len()).step_by(2) {
+        // # Safety
+        // `i` and `i + 1` are in bounds, distinct and not borrowed otherwise.
+        unsafe {
+            let even = v.get_unchecked_mut(i);
+            let odd = v.get_unchecked(i + 1);
+            *even += *odd;
+        }
+    }
+    assert!(v.try_iter().unwrap().eq(&[3, 2, 7, 4]));
+
+    // Nothing was tracked.
+    v.debug_validate();
+    assert!(!v.is_borrowed(0));
+}
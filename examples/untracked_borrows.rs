@@ -0,0 +1,49 @@
+use std::{hint::black_box, time::Instant};
+
+use vec_cell::{BorrowState, Error, VecCell};
+
+// Run with `RUSTFLAGS="--cfg vec_cell_unchecked" cargo run --release --example untracked_borrows`
+// to see tracking turned off, and without the flag to compare the timing printed at the end.
+fn main() {
+    let untracked = cfg!(all(vec_cell_unchecked, not(debug_assertions)));
+    let v = VecCell::from_iter([1, 2]);
+    let a = v.get_mut(0).unwrap();
+
+    // Bounds are always checked.
+    assert_eq!(v.get(2).unwrap_err(), Error::OutOfBounds);
+
+    if untracked {
+        // Taking a borrow doesn't touch the bookkeeping at all, so nothing stops aliasing.
+        assert_eq!(v.borrow_state(0), Ok(BorrowState::None));
+        let guards: Vec<_> = (0..1000).map(|_| v.get_mut(1).unwrap()).collect();
+        assert_eq!(v.borrow_state(1), Ok(BorrowState::None));
+        let validation = v.validate();
+        assert_eq!(validation.unborrowed, 2);
+        assert!(validation.mismatches.is_empty());
+        drop(guards);
+        // Nothing can tell whether the last element is borrowed.
+        assert_eq!(v.try_pop(), Err(Error::Aliasing));
+    } else {
+        // Debug builds keep tracking borrows.
+        assert!(v.is_borrowed_mut(0));
+        assert_eq!(v.get_mut(0).unwrap_err(), Error::Aliasing);
+        assert_eq!(v.try_pop(), Ok(Some(2)));
+    }
+    drop(a);
+
+    // Disjoint accesses in a hot loop, the case the cfg is meant for.
+    let v: VecCell<u64> = (0..1024).collect();
+    let start = Instant::now();
+    for _ in 0..10_000 {
+        for i in (0..v.len()).step_by(2) {
+            let mut even = v.get_mut(black_box(i)).unwrap();
+            let odd = v.get(black_box(i + 1)).unwrap();
+            *even = even.wrapping_add(*odd);
+        }
+    }
+    let elapsed = start.elapsed();
+    black_box(&v);
+    let mode = if untracked { "untracked" } else { "tracked" };
+    let per_borrow = elapsed.as_secs_f64() * 1e9 / (10_000.0 * 1024.0);
+    println!("{mode}: {per_borrow:.2}ns per borrow");
+}
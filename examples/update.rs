@@ -0,0 +1,100 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use vec_cell::{Error, VecCell};
+
+#[derive(Debug, PartialEq)]
+enum CountError {
+    Cell(Error),
+    Overflow,
+}
+
+impl From<Error> for CountError {
+    fn from(err: Error) -> Self {
+        CountError::Cell(err)
+    }
+}
+
+fn main() {
+    let counts = VecCell::from_iter([0u8, 254]);
+
+    counts.update(0, |c| *c += 1).unwrap();
+    assert_eq!(counts.update(0, |c| *c), Ok(1));
+    assert_eq!(counts.update(2, |c| *c).unwrap_err(), Error::OutOfBounds);
+
+    // The element is borrowed while the closure runs, other elements are not.
+    counts
+        .update(0, |_| {
+            assert_eq!(counts.get(0).unwrap_err(), Error::Aliasing);
+            assert_eq!(counts.get_mut(0).unwrap_err(), Error::Aliasing);
+            assert!(counts.get_mut(1).is_ok());
+        })
+        .unwrap();
+
+    // Errors of the closure propagate along with borrow errors.
+    let increment = |c: &mut u8| -> Result<u8, CountError> {
+        *c = c.checked_add(1).ok_or(CountError::Overflow)?;
+        Ok(*c)
+    };
+    assert_eq!(counts.try_update(1, increment), Ok(255));
+    assert_eq!(counts.try_update(1, increment), Err(CountError::Overflow));
+    let a = counts.get(0).unwrap();
+    assert_eq!(
+        counts.try_update(0, increment),
+        Err(CountError::Cell(Error::Aliasing))
+    );
+    drop(a);
+
+    // The element is released even if the closure panics.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        counts.update(0, |_| panic!("update failed"))
+    }));
+    assert!(result.is_err());
+    assert!(!counts.is_borrowed(0));
+    counts.debug_validate();
+    #[cfg(feature = "poison")]
+    counts.clear_poison(0).unwrap();
+
+    // Shared access works alongside other shared borrows only.
+    let a = counts.get(0).unwrap();
+    assert_eq!(counts.with(0, |c| *c), Ok(1));
+    assert_eq!(counts.with(2, |c| *c).unwrap_err(), Error::OutOfBounds);
+    drop(a);
+    let b = counts.get_mut(1).unwrap();
+    assert_eq!(counts.with(1, |c| *c).unwrap_err(), Error::Aliasing);
+    drop(b);
+    counts
+        .with(0, |_| {
+            assert_eq!(counts.get_mut(0).unwrap_err(), Error::Aliasing)
+        })
+        .unwrap();
+    assert!(!counts.is_borrowed(0));
+
+    // Filling is all or nothing.
+    let forces = VecCell::from([1.5, -2.0, 0.5]);
+    let a = forces.get(2).unwrap();
+    assert_eq!(forces.fill(0.0).unwrap_err(), Error::Aliasing);
+    assert_eq!(
+        forces.fill_with(|| unreachable!()).unwrap_err(),
+        Error::Aliasing
+    );
+    assert_eq!(*forces.get(0).unwrap(), 1.5);
+    drop(a);
+    forces.fill(0.0).unwrap();
+    assert!(forces.try_iter().unwrap().all(|f| *f == 0.0));
+    let mut next = 0.0;
+    forces
+        .fill_with(|| {
+            assert_eq!(forces.get(0).unwrap_err(), Error::Aliasing);
+            next += 1.0;
+            next
+        })
+        .unwrap();
+    assert!(forces.try_iter().unwrap().eq(&[1.0, 2.0, 3.0]));
+
+    // Exclusive access skips the checks altogether.
+    let mut scratch = forces;
+    scratch.as_mut_slice().fill(-1.0);
+    scratch.as_mut_slice().fill_with(Default::default);
+    assert!(scratch.try_iter().unwrap().all(|f| *f == 0.0));
+    scratch.debug_validate();
+}
@@ -1,115 +1,1799 @@
-use std::{
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use alloc::{borrow::ToOwned, vec::Vec};
+#[cfg(feature = "debug-borrows")]
+use core::panic::Location;
+use core::{
+    borrow::Borrow,
     cell::{Cell, UnsafeCell},
-    fmt::{self, Display},
-    ops::{Deref, DerefMut},
+    cmp::Ordering,
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::{
+        Bound, Deref, DerefMut, Range, RangeBounds, RangeFrom, RangeFull, RangeInclusive, RangeTo,
+        RangeToInclusive,
+    },
+    ptr::NonNull,
 };
 
-#[derive(Debug)]
-pub struct VecCell<T> {
-    elems: UnsafeCell<Vec<T>>,
-    borrows: Vec<Cell<BorrowState>>,
-    immutable_borrow_count: Cell<usize>,
-    mutable_borrow_count: Cell<usize>,
-}
+#[cfg(feature = "rayon")]
+mod rayon;
+#[cfg(feature = "serde")]
+mod serde;
+mod stable;
+
+// Compiles the README examples as doctests.
+#[cfg(doctest)]
+#[doc = include_str!("../README.md")]
+struct ReadmeDoctests;
+
+#[cfg(feature = "rayon")]
+pub use rayon::ParIter;
+pub use stable::StableVecCell;
+
+#[derive(Debug)]
+pub struct VecCell<T> {
+    elems: UnsafeCell<Vec<T>>,
+    // Only resized through `&self` by `try_push` and `try_pop`, see `VecCell::borrows`.
+    borrows: UnsafeCell<Vec<BorrowFlag>>,
+    // Totals over all elements. Releasing a borrow is decided by the element's own
+    // `BorrowState`, these only answer "is anything borrowed" without scanning `borrows`.
+    immutable_borrow_count: Cell<usize>,
+    mutable_borrow_count: Cell<usize>,
+}
+
+/// Borrow bookkeeping of a single element: either the number of live [`Ref`]s or a [`RefMut`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorrowState {
+    None,
+    Immutable(usize),
+    Mutable,
+}
+
+/// Whether borrows are tracked at all. Building with `--cfg vec_cell_unchecked` turns tracking
+/// off in release builds, leaving it to the user to never alias elements.
+///
+/// This is a `cfg` rather than a feature: features are unified across the dependency graph, so
+/// any crate could turn tracking off for every other user of `VecCell`. A `cfg` passed through
+/// `RUSTFLAGS` is only ever set by whoever builds the final binary.
+const TRACK_BORROWS: bool = cfg!(any(not(vec_cell_unchecked), debug_assertions));
+
+/// Borrow bookkeeping kept next to each element.
+#[derive(Debug, Clone)]
+struct BorrowFlag {
+    state: Cell<BorrowState>,
+    // Number of `RefMut`s beyond the first sharing a mutable borrow, see `RefMut::map_split`.
+    shared_mutable: Cell<usize>,
+    // Where the element was last borrowed, reported when a later borrow conflicts with it.
+    #[cfg(feature = "debug-borrows")]
+    borrowed_at: Cell<Option<&'static Location<'static>>>,
+    // Set when a `RefMut` of the element is dropped during a panic.
+    #[cfg(feature = "poison")]
+    poisoned: Cell<bool>,
+}
+
+/// Immutable borrow of a run of elements, released on drop.
+#[derive(Debug)]
+struct BorrowRef<'b> {
+    borrows: &'b [BorrowFlag],
+    count: &'b Cell<usize>,
+}
+
+/// Mutable borrow of a run of elements, released on drop.
+#[derive(Debug)]
+struct BorrowRefMut<'b> {
+    borrows: &'b [BorrowFlag],
+    count: &'b Cell<usize>,
+    // Whether dropping the guard during a panic poisons its elements. Like `Mutex`, not if the
+    // thread was already panicking when the borrow was taken.
+    #[cfg(feature = "poison")]
+    poison: bool,
+}
+
+/// Immutably borrowed element of a [`VecCell`].
+///
+/// Like [`core::cell::Ref`], it is covariant over both `'b` and `T`:
+///
+/// ```
+/// # use vec_cell::Ref;
+/// fn shorten<'a>(r: Ref<'static, &'static str>) -> Ref<'a, &'a str> {
+///     r
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use vec_cell::Ref;
+/// fn lengthen<'a>(r: Ref<'a, &'a str>) -> Ref<'a, &'static str> {
+///     r
+/// }
+/// ```
+pub struct Ref<'b, T: ?Sized> {
+    // Pointer instead of a reference, so that the element is not asserted to be valid for
+    // the whole of `'b`, only for as long as the guard lives.
+    value: NonNull<T>,
+    _borrow: BorrowRef<'b>,
+}
+
+/// Mutably borrowed element of a [`VecCell`].
+///
+/// Like [`core::cell::RefMut`], it is covariant over `'b` and invariant over `T`. Otherwise a
+/// `RefMut<'_, &'static str>` could be used to store a shorter-lived reference in the element:
+///
+/// ```
+/// # use vec_cell::RefMut;
+/// fn shorten<'a>(r: RefMut<'static, &'a str>) -> RefMut<'a, &'a str> {
+///     r
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use vec_cell::RefMut;
+/// fn shorten<'a>(r: RefMut<'a, &'static str>) -> RefMut<'a, &'a str> {
+///     r
+/// }
+/// ```
+pub struct RefMut<'b, T: ?Sized> {
+    value: NonNull<T>,
+    _borrow: BorrowRefMut<'b>,
+    // `NonNull` is covariant, `&mut T` makes us invariant over `T`.
+    marker: PhantomData<&'b mut T>,
+}
+
+/// Iterator returned by [`VecCell::try_iter`].
+///
+/// Every element stays immutably borrowed until the iterator is dropped, so the yielded
+/// references cannot be aliased by a [`RefMut`].
+#[derive(Debug)]
+pub struct Iter<'a, T> {
+    iter: core::slice::Iter<'a, T>,
+    _borrow: BorrowRef<'a>,
+}
+
+/// Iterator returned by [`VecCell::iter_refs`].
+///
+/// Every element stays immutably borrowed until the iterator is dropped, and each yielded
+/// [`Ref`] keeps its own element borrowed after that.
+#[derive(Debug)]
+pub struct IterRefs<'a, T> {
+    iter: core::slice::Iter<'a, T>,
+    borrow: BorrowRef<'a>,
+}
+
+/// Iterator returned by [`VecCell::try_iter_mut`].
+///
+/// Every element stays mutably borrowed until it is yielded, then until its [`RefMut`] is
+/// dropped. Yielded guards cover distinct elements, so any number of them may be kept alive.
+#[derive(Debug)]
+pub struct IterMut<'a, T> {
+    iter: core::slice::IterMut<'a, T>,
+    borrow: BorrowRefMut<'a>,
+}
+
+/// Iterator returned by [`VecCell::drain`].
+///
+/// The elements after the drained range only get their borrow flags back when the iterator is
+/// dropped, together with the elements themselves. Leaking the iterator leaks those elements,
+/// but never leaves a flag without its element.
+#[derive(Debug)]
+pub struct Drain<'a, T> {
+    iter: alloc::vec::Drain<'a, T>,
+    borrows: &'a mut Vec<BorrowFlag>,
+    tail_len: usize,
+}
+
+/// Report returned by [`VecCell::validate`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validation {
+    /// Number of elements in [`BorrowState::None`].
+    pub unborrowed: usize,
+    /// Number of elements in [`BorrowState::Immutable`].
+    pub immutably_borrowed: usize,
+    /// Number of elements in [`BorrowState::Mutable`].
+    pub mutably_borrowed: usize,
+    /// Internal invariants found broken, in the order they were checked. Empty for a healthy
+    /// `VecCell`, any entry means a bug in this crate rather than in the caller.
+    pub mismatches: Vec<Mismatch>,
+}
+
+/// Internal invariant of a [`VecCell`] found broken by [`VecCell::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mismatch {
+    /// Elements and borrow states are out of sync.
+    Len { elems: usize, borrows: usize },
+    /// Total of immutable borrows disagrees with the per-element states.
+    ImmutableBorrowCount { expected: usize, found: usize },
+    /// Total of mutable borrows disagrees with the per-element states.
+    MutableBorrowCount { expected: usize, found: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    OutOfBounds,
+    Aliasing,
+    /// The same index was passed more than once to a method borrowing several elements
+    /// mutably, like [`VecCell::get_two_mut`].
+    DuplicateIndex,
+    /// The element was mutably borrowed during a panic and may be left half-updated.
+    /// Only returned with the `poison` feature, see `VecCell::clear_poison`.
+    Poisoned,
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Types that can index a [`VecCell`] in [`VecCell::get`] and [`VecCell::get_mut`], like
+/// [`SliceIndex`](core::slice::SliceIndex) does for slices.
+///
+/// Implemented for `usize`, borrowing a single element, and for ranges, borrowing a slice.
+/// This trait is sealed.
+pub trait VecCellIndex<T>: private::Sealed {
+    type Output: ?Sized;
+
+    fn get(self, cell: &VecCell<T>) -> Result<Ref<'_, Self::Output>>;
+
+    fn get_mut(self, cell: &VecCell<T>) -> Result<RefMut<'_, Self::Output>>;
+}
+
+impl<T> VecCell<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            elems: UnsafeCell::new(Vec::with_capacity(capacity)),
+            borrows: UnsafeCell::new(Vec::with_capacity(capacity)),
+            immutable_borrow_count: Cell::new(0),
+            mutable_borrow_count: Cell::new(0),
+        }
+    }
+
+    /// Consumes the `VecCell`, returning the elements without reallocating.
+    pub fn into_vec(self) -> Vec<T> {
+        self.elems.into_inner()
+    }
+
+    /// Consumes the `VecCell`, returning the elements, like [`RefCell::into_inner`].
+    ///
+    /// Equivalent to [`VecCell::into_vec`].
+    ///
+    /// [`RefCell::into_inner`]: core::cell::RefCell::into_inner
+    pub fn into_inner(self) -> Vec<T> {
+        self.into_vec()
+    }
+
+    pub fn len(&self) -> usize {
+        // # Safety
+        // We do not mutate elems here.
+        unsafe { &*self.elems.get() }.len()
+    }
+
+    /// Number of elements the collection can hold without reallocating.
+    ///
+    /// The elements and their borrow flags are stored separately, this is the smaller of the
+    /// two capacities, so [`VecCell::try_push`] succeeds while there is room.
+    pub fn capacity(&self) -> usize {
+        // # Safety
+        // We do not mutate elems or borrows here.
+        let (elems, borrows) = unsafe { (&*self.elems.get(), &*self.borrows.get()) };
+        elems.capacity().min(borrows.capacity())
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        self.elems.get_mut().reserve(additional);
+        self.borrows.get_mut().reserve(additional);
+    }
+
+    /// Reserves capacity for exactly `additional` more elements.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.elems.get_mut().reserve_exact(additional);
+        self.borrows.get_mut().reserve_exact(additional);
+    }
+
+    /// Shrinks the capacity as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.elems.get_mut().shrink_to_fit();
+        self.borrows.get_mut().shrink_to_fit();
+    }
+
+    /// Shrinks the capacity, keeping room for at least `min_capacity` elements.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.elems.get_mut().shrink_to(min_capacity);
+        self.borrows.get_mut().shrink_to(min_capacity);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn push(&mut self, v: T) {
+        let elems = self.elems.get_mut();
+        elems.push(v);
+        self.borrows.get_mut().push(BorrowFlag::new());
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if let Some(borrow) = self.borrows.get_mut().pop() {
+            self.forget_borrows(&borrow);
+        }
+        self.elems.get_mut().pop()
+    }
+
+    /// Appends `v` through a shared reference.
+    ///
+    /// Growing may move the elements, so when there is no spare capacity this fails with
+    /// [`Error::Aliasing`] if any element is borrowed, dropping `v`. Reserving capacity up front
+    /// lets pushes succeed alongside borrows. With `vec_cell_unchecked` in release builds borrows
+    /// aren't tracked, so pushing without spare capacity always fails.
+    pub fn try_push(&self, v: T) -> Result<()> {
+        // # Safety
+        // We do not mutate elems or borrows here.
+        let (elems, borrows) = unsafe { (&*self.elems.get(), &*self.borrows.get()) };
+        debug_assert_eq!(elems.len(), borrows.len(), "elements and borrow flags out of sync");
+        let full = elems.len() == elems.capacity() || borrows.len() == borrows.capacity();
+        let borrowed =
+            self.immutable_borrow_count.get() != 0 || self.mutable_borrow_count.get() != 0;
+        if full && (borrowed || !TRACK_BORROWS) {
+            return Err(Error::Aliasing);
+        }
+        // # Safety
+        // Either nothing is borrowed, or there is room to push without moving any element or
+        // flag. The new slots aren't referenced by any guard.
+        unsafe {
+            (*self.elems.get()).push(v);
+            (*self.borrows.get()).push(BorrowFlag::new());
+        }
+        Ok(())
+    }
+
+    /// Removes the last element through a shared reference.
+    ///
+    /// Fails with [`Error::Aliasing`] if the last element is borrowed, other elements may be,
+    /// since shrinking doesn't move them. Returns `Ok(None)` if empty. With `vec_cell_unchecked`
+    /// in release builds borrows aren't tracked, so popping a non-empty collection always fails.
+    pub fn try_pop(&self) -> Result<Option<T>> {
+        let Some(borrow) = self.borrows().last() else {
+            return Ok(None);
+        };
+        if !TRACK_BORROWS {
+            return Err(Error::Aliasing);
+        }
+        if borrow.is_poisoned() {
+            return Err(Error::Poisoned);
+        }
+        if borrow.get() != BorrowState::None {
+            return Err(Error::Aliasing);
+        }
+        // # Safety
+        // The last element and its flag aren't borrowed, no guard can refer to them. Popping
+        // never reallocates, so pointers to the other elements and flags stay valid.
+        unsafe {
+            (*self.borrows.get()).pop();
+            Ok((*self.elems.get()).pop())
+        }
+    }
+
+    /// Removes the last element through a shared reference if `f` returns `true` for it.
+    ///
+    /// The element is immutably borrowed while `f` runs, so it fails like [`VecCell::get`].
+    /// Fails with [`Error::Aliasing`] if `f` pushes elements that are still there when it
+    /// returns, since the element it saw is no longer the last one.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn pop_if(&self, f: impl FnOnce(&T) -> bool) -> Result<Option<T>> {
+        let Ok(index) = self.last_index() else {
+            return Ok(None);
+        };
+        if !f(&*self.get(index)?) {
+            return Ok(None);
+        }
+        // `f` can't pop the element it saw, but it may have pushed after it.
+        if self.len() != index + 1 {
+            return Err(Error::Aliasing);
+        }
+        self.try_pop()
+    }
+
+    /// Shortens the collection to its first `len` elements. Does nothing if `len` is greater
+    /// than or equal to the current length.
+    pub fn truncate(&mut self, len: usize) {
+        for borrow in self.borrows().get(len..).unwrap_or_default() {
+            self.forget_borrows(borrow);
+        }
+        // `borrows` goes first, as `BorrowFlag` has no drop glue: should dropping an element
+        // panic, `Vec::truncate` has already shortened `elems` to match.
+        self.borrows.get_mut().truncate(len);
+        self.elems.get_mut().truncate(len);
+    }
+
+    /// Resizes the collection to `new_len`, filling new slots with clones of `v`.
+    pub fn resize(&mut self, new_len: usize, v: T)
+    where
+        T: Clone,
+    {
+        self.resize_with(new_len, || v.clone());
+    }
+
+    /// Resizes the collection to `new_len`, filling new slots with the results of calling `f`.
+    pub fn resize_with(&mut self, new_len: usize, mut f: impl FnMut() -> T) {
+        let len = self.len();
+        if new_len <= len {
+            self.truncate(new_len);
+            return;
+        }
+        self.reserve(new_len - len);
+        // One element at a time, so that a panicking `f` leaves every element with its flag.
+        for _ in len..new_len {
+            self.push(f());
+        }
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, preserving their order.
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        // Decide up front, so that a panicking `f` leaves everything untouched.
+        let keep: Vec<bool> = self.elems.get_mut().iter().map(&mut f).collect();
+        // Move the kept elements to the front in order, each flag along with its element.
+        let (elems, borrows) = (self.elems.get_mut(), self.borrows.get_mut());
+        let mut kept = 0;
+        for (index, _) in keep.iter().enumerate().filter(|(_, &keep)| keep) {
+            elems.swap(kept, index);
+            borrows.swap(kept, index);
+            kept += 1;
+        }
+        // Only the rejected elements are dropped, and should dropping one of them panic,
+        // `truncate` still leaves both vectors the same length.
+        self.truncate(kept);
+    }
+
+    /// Removes the elements in `range`, returning them as an iterator.
+    ///
+    /// The elements are removed even if the iterator is not fully consumed. Borrows leaked on
+    /// the elements after the range are released, like those of the removed elements. If the
+    /// iterator is leaked, so are the elements after the range.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds or its start is greater than its end.
+    pub fn drain(&mut self, range: impl RangeBounds<usize>) -> Drain<'_, T> {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+        let start = match range.0 {
+            Bound::Included(start) => start,
+            Bound::Excluded(start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        // An invalid range is left for `Vec::drain` to panic on, before anything changes.
+        if self.borrows().get(range).is_some() {
+            for borrow in &self.borrows()[start..] {
+                self.forget_borrows(borrow);
+            }
+        }
+        let iter = self.elems.get_mut().drain(range);
+        // `Vec::drain` cuts `elems` down to the start of the range until `iter` is dropped.
+        // Cut `borrows` down to match, so that leaking `iter` can't leave flags for elements
+        // that are gone.
+        let borrows = self.borrows.get_mut();
+        let tail_len = borrows.len() - start - iter.len();
+        borrows.truncate(start);
+        Drain {
+            iter,
+            borrows,
+            tail_len,
+        }
+    }
+
+    /// Removes all elements, keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        // `borrows` goes first, see `VecCell::truncate`.
+        self.borrows.get_mut().clear();
+        self.elems.get_mut().clear();
+        // Any borrows still counted leaked along with their elements.
+        *self.immutable_borrow_count.get_mut() = 0;
+        *self.mutable_borrow_count.get_mut() = 0;
+    }
+
+    /// Inserts `v` at `index`, shifting all elements after it to the right.
+    ///
+    /// # Panics
+    /// Panics if `index > len`, like [`Vec::insert`].
+    #[track_caller]
+    pub fn insert(&mut self, index: usize, v: T) {
+        let elems = self.elems.get_mut();
+        elems.insert(index, v);
+        self.borrows.get_mut().insert(index, BorrowFlag::new());
+    }
+
+    /// Removes and returns the element at `index`, shifting all elements after it to the left.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds, like [`Vec::remove`]. Editing methods take
+    /// `&mut self` and panic like `Vec` does, only borrows report errors through [`Result`].
+    #[track_caller]
+    pub fn remove(&mut self, index: usize) -> T {
+        let v = self.elems.get_mut().remove(index);
+        let borrow = self.borrows.get_mut().remove(index);
+        self.forget_borrows(&borrow);
+        v
+    }
+
+    /// Removes and returns the element at `index`, replacing it with the last element.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds, like [`Vec::swap_remove`].
+    #[track_caller]
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        // `Vec::swap_remove` doesn't report its caller, so check here.
+        let len = self.len();
+        if index >= len {
+            panic!("swap_remove index (is {index}) should be < len (is {len})");
+        }
+        let v = self.elems.get_mut().swap_remove(index);
+        let borrow = self.borrows.get_mut().swap_remove(index);
+        self.forget_borrows(&borrow);
+        v
+    }
+
+    /// Releases borrows leaked by forgotten guards, returning how many elements were borrowed.
+    ///
+    /// Taking `&mut self` proves no guard is alive, so every remaining borrow must have leaked.
+    pub fn reset_borrows(&mut self) -> usize {
+        let mut cleared = 0;
+        for borrow in self.borrows.get_mut() {
+            *borrow.shared_mutable.get_mut() = 0;
+            let state = borrow.state.get_mut();
+            if *state != BorrowState::None {
+                *state = BorrowState::None;
+                cleared += 1;
+            }
+        }
+        *self.immutable_borrow_count.get_mut() = 0;
+        *self.mutable_borrow_count.get_mut() = 0;
+        cleared
+    }
+
+    /// Current borrow state of the element at `index`, without borrowing it.
+    pub fn borrow_state(&self, index: usize) -> Result<BorrowState> {
+        let borrow = self.borrows().get(index).ok_or(Error::OutOfBounds)?;
+        Ok(borrow.get())
+    }
+
+    /// Whether the element at `index` is borrowed in any way. `false` when out of bounds.
+    pub fn is_borrowed(&self, index: usize) -> bool {
+        self.borrow_state(index)
+            .is_ok_and(|state| state != BorrowState::None)
+    }
+
+    /// Whether the element at `index` is mutably borrowed. `false` when out of bounds.
+    pub fn is_borrowed_mut(&self, index: usize) -> bool {
+        self.borrow_state(index) == Ok(BorrowState::Mutable)
+    }
+
+    /// Whether a panic happened while the element at `index` was mutably borrowed.
+    /// `false` when out of bounds.
+    #[cfg(feature = "poison")]
+    pub fn is_poisoned(&self, index: usize) -> bool {
+        self.borrows()
+            .get(index)
+            .is_some_and(BorrowFlag::is_poisoned)
+    }
+
+    /// Makes the element at `index` available again after it was poisoned.
+    #[cfg(feature = "poison")]
+    pub fn clear_poison(&self, index: usize) -> Result<()> {
+        let borrow = self.borrows().get(index).ok_or(Error::OutOfBounds)?;
+        borrow.poisoned.set(false);
+        Ok(())
+    }
+
+    /// Recomputes the borrow bookkeeping from per-element states and reports any disagreement.
+    ///
+    /// Doesn't borrow any element, so it can be called while guards are alive.
+    pub fn validate(&self) -> Validation {
+        let mut report = Validation::default();
+        let mut immutable_borrows = 0usize;
+        for borrow in self.borrows() {
+            match borrow.get() {
+                BorrowState::None => report.unborrowed += 1,
+                BorrowState::Immutable(count) => {
+                    report.immutably_borrowed += 1;
+                    immutable_borrows = immutable_borrows.saturating_add(count);
+                }
+                BorrowState::Mutable => report.mutably_borrowed += 1,
+            }
+        }
+        if self.len() != self.borrows().len() {
+            report.mismatches.push(Mismatch::Len {
+                elems: self.len(),
+                borrows: self.borrows().len(),
+            });
+        }
+        if self.immutable_borrow_count.get() != immutable_borrows {
+            report.mismatches.push(Mismatch::ImmutableBorrowCount {
+                expected: immutable_borrows,
+                found: self.immutable_borrow_count.get(),
+            });
+        }
+        if self.mutable_borrow_count.get() != report.mutably_borrowed {
+            report.mismatches.push(Mismatch::MutableBorrowCount {
+                expected: report.mutably_borrowed,
+                found: self.mutable_borrow_count.get(),
+            });
+        }
+        report
+    }
+
+    /// Runs [`VecCell::validate`] and panics on any mismatch in debug builds.
+    pub fn debug_validate(&self) {
+        if cfg!(debug_assertions) {
+            let report = self.validate();
+            assert!(
+                report.mismatches.is_empty(),
+                "VecCell invariants broken: {:?}",
+                report.mismatches
+            );
+        }
+    }
+
+    /// Where the element at `index` was last borrowed, if it is currently borrowed.
+    #[cfg(feature = "debug-borrows")]
+    pub fn borrowed_at(&self, index: usize) -> Option<&'static Location<'static>> {
+        let borrow = self.borrows().get(index)?;
+        if borrow.get() == BorrowState::None {
+            None
+        } else {
+            borrow.borrowed_at.get()
+        }
+    }
+
+    /// Immutably borrows an element, or a slice of elements if `index` is a range.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn get<I: VecCellIndex<T>>(&self, index: I) -> Result<Ref<'_, I::Output>> {
+        index.get(self)
+    }
+
+    /// Mutably borrows an element, or a slice of elements if `index` is a range.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn get_mut<I: VecCellIndex<T>>(&self, index: I) -> Result<RefMut<'_, I::Output>> {
+        index.get_mut(self)
+    }
+
+    /// Reference to the element at `index`, without bounds or borrow checks.
+    ///
+    /// # Safety
+    /// `index` must be in bounds and, while the returned reference lives, the element must not
+    /// be mutated, neither through a [`RefMut`] nor through [`VecCell::get_unchecked_mut`].
+    /// The reference is not tracked, so `VecCell` itself won't prevent that.
+    #[inline]
+    pub unsafe fn get_unchecked(&self, index: usize) -> &T {
+        debug_assert!(!self.is_borrowed_mut(index));
+        // # Safety
+        // Upheld by the caller.
+        unsafe { self.elem_ptr(index).as_ref() }
+    }
+
+    /// Mutable reference to the element at `index`, without bounds or borrow checks.
+    ///
+    /// # Safety
+    /// `index` must be in bounds and, while the returned reference lives, the element must not
+    /// be accessed in any other way, neither through guards nor through
+    /// [`VecCell::get_unchecked`] or [`VecCell::get_unchecked_mut`].
+    /// The reference is not tracked, so `VecCell` itself won't prevent that.
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn get_unchecked_mut(&self, index: usize) -> &mut T {
+        debug_assert!(!self.is_borrowed(index));
+        // # Safety
+        // Upheld by the caller.
+        unsafe { self.elem_ptr(index).as_mut() }
+    }
+
+    /// Immutably borrows the elements in `range` as a slice.
+    ///
+    /// Ranges may overlap with other immutable borrows, but not with mutable ones.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn get_range(&self, range: impl RangeBounds<usize>) -> Result<Ref<'_, [T]>> {
+        let range = self.resolve_range(range)?;
+        let borrows = &self.borrows()[range.clone()];
+        let borrow = BorrowRef::new(borrows, &self.immutable_borrow_count)?;
+        Ok(Ref {
+            value: self.slice_ptr(range),
+            _borrow: borrow,
+        })
+    }
+
+    /// Mutably borrows the elements in `range` as a slice.
+    ///
+    /// Disjoint ranges can be borrowed mutably at the same time; each guard releases only its own
+    /// elements when dropped.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn get_range_mut(&self, range: impl RangeBounds<usize>) -> Result<RefMut<'_, [T]>> {
+        let range = self.resolve_range(range)?;
+        let borrows = &self.borrows()[range.clone()];
+        let borrow = BorrowRefMut::new(borrows, &self.mutable_borrow_count)?;
+        Ok(RefMut {
+            value: self.slice_ptr(range),
+            _borrow: borrow,
+            marker: PhantomData,
+        })
+    }
+
+    /// Immutably borrows the first element, failing with [`Error::OutOfBounds`] if empty.
+    ///
+    /// Unlike [`slice::first`], an empty collection is reported as an error, so that it can be
+    /// propagated with `?` next to borrow errors.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn first(&self) -> Result<Ref<'_, T>> {
+        self.get(0)
+    }
+
+    /// Immutably borrows the last element, failing with [`Error::OutOfBounds`] if empty.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn last(&self) -> Result<Ref<'_, T>> {
+        self.get(self.last_index()?)
+    }
+
+    /// Splits into a borrow of the first element and a borrow of the rest.
+    /// Fails with [`Error::OutOfBounds`] if empty.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn split_first(&self) -> Result<(Ref<'_, T>, Ref<'_, [T]>)> {
+        let first = self.get(0)?;
+        let rest = self.get_range(1..self.len())?;
+        Ok((first, rest))
+    }
+
+    /// Splits into a borrow of the last element and a borrow of the rest.
+    /// Fails with [`Error::OutOfBounds`] if empty.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn split_last(&self) -> Result<(Ref<'_, T>, Ref<'_, [T]>)> {
+        let index = self.last_index()?;
+        let last = self.get(index)?;
+        let rest = self.get_range(0..index)?;
+        Ok((last, rest))
+    }
+
+    /// Mutable counterpart of [`VecCell::split_first`].
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn split_first_mut(&self) -> Result<(RefMut<'_, T>, RefMut<'_, [T]>)> {
+        let first = self.get_mut(0)?;
+        let rest = self.get_range_mut(1..self.len())?;
+        Ok((first, rest))
+    }
+
+    /// Mutable counterpart of [`VecCell::split_last`].
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn split_last_mut(&self) -> Result<(RefMut<'_, T>, RefMut<'_, [T]>)> {
+        let index = self.last_index()?;
+        let last = self.get_mut(index)?;
+        let rest = self.get_range_mut(0..index)?;
+        Ok((last, rest))
+    }
+
+    /// Immutably borrows the elements before and after `mid` as two slices.
+    ///
+    /// Fails with [`Error::OutOfBounds`] if `mid > len`; either half may be empty.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    #[allow(clippy::type_complexity)]
+    pub fn split_at(&self, mid: usize) -> Result<(Ref<'_, [T]>, Ref<'_, [T]>)> {
+        let left = self.get_range(..mid)?;
+        let right = self.get_range(mid..)?;
+        Ok((left, right))
+    }
+
+    /// Mutable counterpart of [`VecCell::split_at`].
+    ///
+    /// Nothing is left borrowed if either half fails to borrow.
+    /// With exclusive access, `as_mut_slice().split_at_mut(mid)` does the same without borrow
+    /// checks.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    #[allow(clippy::type_complexity)]
+    pub fn split_at_mut(&self, mid: usize) -> Result<(RefMut<'_, [T]>, RefMut<'_, [T]>)> {
+        let left = self.get_range_mut(..mid)?;
+        let right = self.get_range_mut(mid..)?;
+        Ok((left, right))
+    }
+
+    /// Mutably borrows the first element, failing with [`Error::OutOfBounds`] if empty.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn first_mut(&self) -> Result<RefMut<'_, T>> {
+        self.get_mut(0)
+    }
+
+    /// Mutably borrows the last element, failing with [`Error::OutOfBounds`] if empty.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn last_mut(&self) -> Result<RefMut<'_, T>> {
+        self.get_mut(self.last_index()?)
+    }
+
+    /// Immutably borrows the element at `index`.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds or the element is mutably borrowed.
+    /// For a non-panicking variant use [`VecCell::get`].
+    #[track_caller]
+    pub fn borrow(&self, index: usize) -> Ref<'_, T> {
+        match self.get(index) {
+            Ok(r) => r,
+            Err(err) => self.borrow_failed(index, err),
+        }
+    }
+
+    /// Mutably borrows the element at `index`.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds or the element is borrowed.
+    /// For a non-panicking variant use [`VecCell::get_mut`].
+    #[track_caller]
+    pub fn borrow_mut(&self, index: usize) -> RefMut<'_, T> {
+        match self.get_mut(index) {
+            Ok(r) => r,
+            Err(err) => self.borrow_failed(index, err),
+        }
+    }
+
+    /// Mutably borrows the elements at `a` and `b` at once. If either cannot be borrowed,
+    /// neither is.
+    ///
+    /// Fails with [`Error::DuplicateIndex`] if `a == b`, since that would alias the element.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn get_two_mut(&self, a: usize, b: usize) -> Result<(RefMut<'_, T>, RefMut<'_, T>)> {
+        let [a, b] = self.get_disjoint_mut([a, b])?;
+        Ok((a, b))
+    }
+
+    /// Mutably borrows all `indices` at once. If any of them cannot be borrowed, none are.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn get_disjoint_mut<const N: usize>(
+        &self,
+        indices: [usize; N],
+    ) -> Result<[RefMut<'_, T>; N]> {
+        if indices.iter().any(|&index| index >= self.len()) {
+            return Err(Error::OutOfBounds);
+        }
+        for (i, index) in indices.iter().enumerate() {
+            if indices[..i].contains(index) {
+                return Err(Error::DuplicateIndex);
+            }
+        }
+        if indices
+            .iter()
+            .any(|&index| self.borrows()[index].is_poisoned())
+        {
+            return Err(Error::Poisoned);
+        }
+        if indices
+            .iter()
+            .any(|&index| self.borrows()[index].get() != BorrowState::None)
+        {
+            return Err(Error::Aliasing);
+        }
+        let refs = indices.map(|index| {
+            self.get_mut(index)
+                .expect("indices are in bounds, distinct and not borrowed")
+        });
+        // The closure above hides our caller from `get_mut`.
+        for index in indices {
+            self.borrows()[index].record_caller();
+        }
+        Ok(refs)
+    }
+
+    /// Like [`VecCell::get_disjoint_mut`], but reports duplicate indices as
+    /// [`Error::Aliasing`], since borrowing an element twice would alias it.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn get_many_mut<const N: usize>(&self, indices: [usize; N]) -> Result<[RefMut<'_, T>; N]> {
+        self.get_disjoint_mut(indices).map_err(|err| match err {
+            Error::DuplicateIndex => Error::Aliasing,
+            err => err,
+        })
+    }
+
+    /// Mutably borrows every range of elements at once. If any of them cannot be borrowed,
+    /// none are.
+    ///
+    /// Overlapping ranges would alias elements and fail with [`Error::Aliasing`].
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn get_disjoint_ranges_mut(&self, ranges: &[Range<usize>]) -> Result<Vec<RefMut<'_, [T]>>> {
+        if ranges
+            .iter()
+            .any(|range| range.start > range.end || range.end > self.len())
+        {
+            return Err(Error::OutOfBounds);
+        }
+        for (i, range) in ranges.iter().enumerate() {
+            if ranges[..i].iter().any(|other| overlaps(range, other)) {
+                return Err(Error::Aliasing);
+            }
+        }
+        let borrows = || {
+            ranges
+                .iter()
+                .flat_map(|range| &self.borrows()[range.clone()])
+        };
+        if borrows().any(BorrowFlag::is_poisoned) {
+            return Err(Error::Poisoned);
+        }
+        if borrows().any(|borrow| borrow.get() != BorrowState::None) {
+            return Err(Error::Aliasing);
+        }
+        let refs = ranges
+            .iter()
+            .map(|range| {
+                self.get_range_mut(range.clone())
+                    .expect("ranges are in bounds, disjoint and not borrowed")
+            })
+            .collect();
+        // The closure above hides our caller from `get_range_mut`.
+        for borrow in borrows() {
+            borrow.record_caller();
+        }
+        Ok(refs)
+    }
+
+    /// Swaps two elements, failing with [`Error::Aliasing`] if either is borrowed, immutably
+    /// or mutably, as a live guard would suddenly see a different value.
+    ///
+    /// Swapping an element with itself does nothing.
+    pub fn swap(&self, a: usize, b: usize) -> Result<()> {
+        if a == b {
+            return if a < self.len() {
+                Ok(())
+            } else {
+                Err(Error::OutOfBounds)
+            };
+        }
+        let (mut a, mut b) = self.get_two_mut(a, b)?;
+        core::mem::swap(&mut *a, &mut *b);
+        Ok(())
+    }
+
+    /// Swaps all elements with those of `other`, like [`slice::swap_with_slice`].
+    ///
+    /// Fails with [`Error::OutOfBounds`] if the lengths differ and with [`Error::Aliasing`] if any
+    /// element is borrowed, swapping nothing in either case.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn swap_with_slice(&self, other: &mut [T]) -> Result<()> {
+        if other.len() != self.len() {
+            return Err(Error::OutOfBounds);
+        }
+        self.get_range_mut(..)?.swap_with_slice(other);
+        Ok(())
+    }
+
+    /// Copies all elements from `src`, like [`slice::copy_from_slice`].
+    ///
+    /// Fails with [`Error::OutOfBounds`] if the lengths differ and with [`Error::Aliasing`] if any
+    /// element is borrowed, writing nothing in either case.
+    /// With exclusive access, `as_mut_slice().copy_from_slice(src)` skips the borrow checks and
+    /// panics on a length mismatch instead.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn copy_from_slice(&self, src: &[T]) -> Result<()>
+    where
+        T: Copy,
+    {
+        if src.len() != self.len() {
+            return Err(Error::OutOfBounds);
+        }
+        self.get_range_mut(..)?.copy_from_slice(src);
+        Ok(())
+    }
+
+    /// Clones all elements from `src` with [`Clone::clone_from`], so that their allocations can
+    /// be reused. Fails like [`VecCell::copy_from_slice`].
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn clone_from_slice(&self, src: &[T]) -> Result<()>
+    where
+        T: Clone,
+    {
+        if src.len() != self.len() {
+            return Err(Error::OutOfBounds);
+        }
+        self.get_range_mut(..)?.clone_from_slice(src);
+        Ok(())
+    }
+
+    /// Overwrites every element with a clone of `v`.
+    ///
+    /// Fails with [`Error::Aliasing`] if any element is borrowed, writing nothing.
+    /// With exclusive access, `as_mut_slice().fill(v)` does the same without borrow checks.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn fill(&self, v: T) -> Result<()>
+    where
+        T: Clone,
+    {
+        self.try_as_mut_slice()?.fill(v);
+        Ok(())
+    }
+
+    /// Overwrites every element with the result of calling `f`, in index order.
+    ///
+    /// Fails with [`Error::Aliasing`] if any element is borrowed, without calling `f`.
+    /// All elements stay borrowed while `f` runs. With exclusive access,
+    /// `as_mut_slice().fill_with(f)` does the same without borrow checks.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn fill_with(&self, f: impl FnMut() -> T) -> Result<()> {
+        self.try_as_mut_slice()?.fill_with(f);
+        Ok(())
+    }
+
+    /// Copies the elements in `src` to `dest`, like [`slice::copy_within`]. The ranges may
+    /// overlap.
+    ///
+    /// Fails with [`Error::OutOfBounds`] if either range doesn't fit, and with
+    /// [`Error::Aliasing`] if a destination element is borrowed or a source element is mutably
+    /// borrowed. Overlapping ranges are written as one, so none of their elements may be borrowed.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn copy_within(&self, src: impl RangeBounds<usize>, dest: usize) -> Result<()>
+    where
+        T: Copy,
+    {
+        let (src, dest) = self.resolve_within(src, dest)?;
+        if overlaps(&src, &dest) {
+            let start = src.start.min(dest.start);
+            let mut span = self.get_range_mut(start..src.end.max(dest.end))?;
+            span.copy_within(src.start - start..src.end - start, dest.start - start);
+        } else {
+            let src = self.get_range(src)?;
+            self.get_range_mut(dest)?.copy_from_slice(&src);
+        }
+        Ok(())
+    }
+
+    /// Clones the elements in `src` to `dest` one by one, like [`VecCell::copy_within`] does for
+    /// `Copy` types.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn clone_within(&self, src: impl RangeBounds<usize>, dest: usize) -> Result<()>
+    where
+        T: Clone,
+    {
+        let (src, dest) = self.resolve_within(src, dest)?;
+        if overlaps(&src, &dest) {
+            let start = src.start.min(dest.start);
+            let mut span = self.get_range_mut(start..src.end.max(dest.end))?;
+            let (src, dest) = (src.start - start, dest.start - start);
+            let len = span.len() - src.abs_diff(dest);
+            // Elements are overwritten only after being cloned, like `ptr::copy` does.
+            if dest < src {
+                for i in 0..len {
+                    span[dest + i] = span[src + i].clone();
+                }
+            } else {
+                for i in (0..len).rev() {
+                    span[dest + i] = span[src + i].clone();
+                }
+            }
+        } else {
+            let src = self.get_range(src)?;
+            self.get_range_mut(dest)?.clone_from_slice(&src);
+        }
+        Ok(())
+    }
+
+    /// Overwrites the element at `index` with `v`, dropping the previous value.
+    ///
+    /// Fails like [`VecCell::get_mut`], other elements may be borrowed.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn set(&self, index: usize, v: T) -> Result<()> {
+        *self.get_mut(index)? = v;
+        Ok(())
+    }
+
+    /// Replaces the element at `index` with `v`, returning the previous value.
+    ///
+    /// Fails with [`Error::Aliasing`] if the element is borrowed, other elements may be.
+    /// The element is only borrowed for the duration of the call.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn replace(&self, index: usize, v: T) -> Result<T> {
+        let mut elem = self.get_mut(index)?;
+        Ok(core::mem::replace(&mut *elem, v))
+    }
+
+    /// Clones the element at `index`. Fails only if the element is mutably borrowed.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn get_cloned(&self, index: usize) -> Result<T>
+    where
+        T: Clone,
+    {
+        self.with(index, T::clone)
+    }
+
+    /// Immutably borrows the first element for which `f` returns `true`.
+    ///
+    /// Fails with [`Error::Aliasing`] if an element that has to be checked is mutably borrowed.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn try_find(&self, mut f: impl FnMut(&T) -> bool) -> Result<Option<Ref<'_, T>>> {
+        for index in 0..self.len() {
+            let elem = self.get(index)?;
+            if f(&elem) {
+                return Ok(Some(elem));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Index of the first element for which `f` returns `true`, see [`VecCell::try_find`].
+    ///
+    /// Each element is only borrowed while `f` checks it.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn position(&self, mut f: impl FnMut(&T) -> bool) -> Result<Option<usize>> {
+        for index in 0..self.len() {
+            if self.with(index, &mut f)? {
+                return Ok(Some(index));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Binary searches the sorted elements for `x`, like [`slice::binary_search`].
+    ///
+    /// The outer `Result` fails with [`Error::Aliasing`] if a probed element is mutably
+    /// borrowed, the inner one is the search result.
+    pub fn binary_search(&self, x: &T) -> Result<core::result::Result<usize, usize>>
+    where
+        T: Ord,
+    {
+        self.binary_search_by(|elem| elem.cmp(x))
+    }
+
+    /// Binary searches the elements with a comparator, like [`slice::binary_search_by`].
+    ///
+    /// Only the probed elements are borrowed, one at a time.
+    pub fn binary_search_by(
+        &self,
+        mut f: impl FnMut(&T) -> Ordering,
+    ) -> Result<core::result::Result<usize, usize>> {
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.with(mid, &mut f)? {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Ok(Ok(mid)),
+            }
+        }
+        Ok(Err(low))
+    }
+
+    /// Binary searches the elements sorted by a key, like [`slice::binary_search_by_key`].
+    pub fn binary_search_by_key<B: Ord>(
+        &self,
+        b: &B,
+        mut f: impl FnMut(&T) -> B,
+    ) -> Result<core::result::Result<usize, usize>> {
+        self.binary_search_by(|elem| f(elem).cmp(b))
+    }
+
+    /// Whether any element equals `value`.
+    ///
+    /// Stops at the first match, failing with [`Error::Aliasing`] only if an element before it
+    /// is mutably borrowed.
+    pub fn contains<Q>(&self, value: &Q) -> Result<bool>
+    where
+        T: Borrow<Q>,
+        Q: PartialEq + ?Sized,
+    {
+        Ok(self.try_find(|elem| elem.borrow() == value)?.is_some())
+    }
+
+    /// Mutably borrows the first element for which `f` returns `true`.
+    ///
+    /// Fails with [`Error::Aliasing`] if an element that has to be checked is mutably borrowed,
+    /// or if the match is immutably borrowed elsewhere.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn try_find_mut(&self, mut f: impl FnMut(&T) -> bool) -> Result<Option<RefMut<'_, T>>> {
+        for index in 0..self.len() {
+            if self.with(index, &mut f)? {
+                return self.get_mut(index).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Clones all elements into a new `VecCell` without any borrows.
+    ///
+    /// Fails with [`Error::Aliasing`] if any element is mutably borrowed.
+    pub fn try_clone(&self) -> Result<Self>
+    where
+        T: Clone,
+    {
+        Ok(self.try_iter()?.cloned().collect())
+    }
+
+    /// Clones all elements into a `Vec`.
+    ///
+    /// Fails with [`Error::Aliasing`] if any element is mutably borrowed.
+    pub fn try_to_vec(&self) -> Result<Vec<T>>
+    where
+        T: Clone,
+    {
+        Ok(self.try_as_slice()?.to_vec())
+    }
+
+    /// Clones all elements into `buf`, replacing its contents and reusing its allocation.
+    ///
+    /// Fails with [`Error::Aliasing`] if any element is mutably borrowed, leaving `buf` as is.
+    pub fn try_clone_into(&self, buf: &mut Vec<T>) -> Result<()>
+    where
+        T: Clone,
+    {
+        self.try_as_slice()?.clone_into(buf);
+        Ok(())
+    }
+
+    /// Maps every element into a new `VecCell` without any borrows.
+    ///
+    /// Fails with [`Error::Aliasing`] if any element is mutably borrowed.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Result<VecCell<U>> {
+        Ok(self.try_iter()?.map(f).collect())
+    }
+
+    /// Runs `f` on a shared reference to the element at `index`, returning its result.
+    ///
+    /// The element is borrowed only while `f` runs, even if it panics.
+    ///
+    /// ```
+    /// # use vec_cell::VecCell;
+    /// let v = VecCell::from_iter([1, 2, 3]);
+    /// let mut sum = 0;
+    /// for i in 0..v.len() {
+    ///     sum += v.with(i, |x| *x).unwrap();
+    /// }
+    /// assert_eq!(sum, 6);
+    /// ```
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn with<R>(&self, index: usize, f: impl FnOnce(&T) -> R) -> Result<R> {
+        let elem = self.get(index)?;
+        Ok(f(&elem))
+    }
+
+    /// Runs `f` on the element at `index`, returning its result.
+    ///
+    /// The element stays mutably borrowed while `f` runs, so `f` can't borrow it again.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn update<R>(&self, index: usize, f: impl FnOnce(&mut T) -> R) -> Result<R> {
+        let mut elem = self.get_mut(index)?;
+        Ok(f(&mut elem))
+    }
+
+    /// Like [`VecCell::update`], but for a fallible `f`. Borrow errors are converted into `E`.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn try_update<R, E: From<Error>>(
+        &self,
+        index: usize,
+        f: impl FnOnce(&mut T) -> core::result::Result<R, E>,
+    ) -> core::result::Result<R, E> {
+        let mut elem = self.get_mut(index)?;
+        f(&mut elem)
+    }
+
+    /// Takes the element at `index`, leaving `T::default()` in its place.
+    ///
+    /// Fails like [`VecCell::replace`]: with [`Error::Aliasing`] if the element is borrowed,
+    /// other elements may be.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn take(&self, index: usize) -> Result<T>
+    where
+        T: Default,
+    {
+        self.replace_with(index, |_| T::default())
+    }
+
+    /// Replaces the element at `index` with the value computed by `f` from the current one,
+    /// returning the previous value.
+    ///
+    /// The element stays mutably borrowed while `f` runs, even if it panics, so `f` can't
+    /// borrow it again.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn replace_with(&self, index: usize, f: impl FnOnce(&mut T) -> T) -> Result<T> {
+        let mut elem = self.get_mut(index)?;
+        let v = f(&mut elem);
+        Ok(core::mem::replace(&mut *elem, v))
+    }
+
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn try_iter(&self) -> Result<Iter<'_, T>> {
+        let borrow = self.borrow_all()?;
+        // # Safety
+        // Every element is now immutably borrowed until `Iter` is dropped.
+        // Aliasing rules allow multiple immutable borrows.
+        let elems = unsafe { &*self.elems.get() };
+        Ok(Iter {
+            iter: elems[..self.tracked_len()].iter(),
+            _borrow: borrow,
+        })
+    }
+
+    /// Immutably borrows every element, yielding a [`Ref`] for each.
+    ///
+    /// Fails with [`Error::Aliasing`] if any element is mutably borrowed.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn iter_refs(&self) -> Result<IterRefs<'_, T>> {
+        let borrow = self.borrow_all()?;
+        // # Safety
+        // Every element is now immutably borrowed until it is yielded, and then until its
+        // guard is dropped.
+        let elems = unsafe { &*self.elems.get() };
+        Ok(IterRefs {
+            iter: elems[..self.tracked_len()].iter(),
+            borrow,
+        })
+    }
+
+    /// Mutably borrows every element, yielding a [`RefMut`] for each.
+    ///
+    /// Fails with [`Error::Aliasing`] if any element is borrowed. Unlike
+    /// [`VecCell::iter_mut`] this only needs `&self`.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn try_iter_mut(&self) -> Result<IterMut<'_, T>> {
+        if self.immutable_borrow_count.get() != 0 || self.mutable_borrow_count.get() != 0 {
+            return Err(Error::Aliasing);
+        }
+        let borrow = BorrowRefMut::new(self.borrows(), &self.mutable_borrow_count)?;
+        // # Safety
+        // Every element is now mutably borrowed until it is yielded, and then until its
+        // guard is dropped.
+        let len = self.tracked_len();
+        let elems = unsafe { &mut *self.elems.get() };
+        Ok(IterMut {
+            iter: elems[..len].iter_mut(),
+            borrow,
+        })
+    }
+
+    /// Immutably borrows all elements as a slice, failing with [`Error::Aliasing`] if any of them
+    /// is mutably borrowed.
+    ///
+    /// The slice is returned behind a guard rather than as a plain `&[T]`, since otherwise the
+    /// elements could be mutably borrowed while the slice is still in use.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn try_as_slice(&self) -> Result<Ref<'_, [T]>> {
+        let borrow = self.borrow_all()?;
+        Ok(Ref {
+            value: self.slice_ptr(0..self.tracked_len()),
+            _borrow: borrow,
+        })
+    }
+
+    /// Mutably borrows all elements as a slice, failing with [`Error::Aliasing`] if any of them
+    /// is borrowed.
+    ///
+    /// The guard gives access to the elements only, the collection can't grow or shrink through
+    /// it.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn try_as_mut_slice(&self) -> Result<RefMut<'_, [T]>> {
+        if self.immutable_borrow_count.get() != 0 || self.mutable_borrow_count.get() != 0 {
+            return Err(Error::Aliasing);
+        }
+        self.get_range_mut(..)
+    }
+
+    /// Immutably borrows every element separately, so that each guard can be dropped on its own.
+    ///
+    /// Fails with [`Error::Aliasing`] if any element is mutably borrowed, leaving nothing borrowed.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn each_ref(&self) -> Result<Vec<Ref<'_, T>>> {
+        if self.mutable_borrow_count.get() != 0 {
+            return Err(Error::Aliasing);
+        }
+        let mut refs = Vec::with_capacity(self.len());
+        for index in 0..self.len() {
+            // Guards collected so far are released if this fails.
+            refs.push(self.get(index)?);
+        }
+        Ok(refs)
+    }
+
+    /// Mutable counterpart of [`VecCell::each_ref`].
+    ///
+    /// Fails with [`Error::Aliasing`] if any element is borrowed, leaving nothing borrowed.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn each_mut(&self) -> Result<Vec<RefMut<'_, T>>> {
+        if self.immutable_borrow_count.get() != 0 || self.mutable_borrow_count.get() != 0 {
+            return Err(Error::Aliasing);
+        }
+        let mut refs = Vec::with_capacity(self.len());
+        for index in 0..self.len() {
+            refs.push(self.get_mut(index)?);
+        }
+        Ok(refs)
+    }
+
+    /// Number of elements covered by borrow flags, which borrowing everything must stick to.
+    /// Differs from `len` only if an invariant is broken.
+    fn tracked_len(&self) -> usize {
+        let len = self.borrows().len();
+        debug_assert_eq!(len, self.len(), "elements and borrow flags out of sync");
+        len.min(self.len())
+    }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum BorrowState {
-    None,
-    Immutable,
-    Mutable,
-}
+    /// Immutably borrows every element, checking the mutable borrow count first to fail fast.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    fn borrow_all(&self) -> Result<BorrowRef<'_>> {
+        if self.mutable_borrow_count.get() != 0 {
+            return Err(Error::Aliasing);
+        }
+        BorrowRef::new(self.borrows(), &self.immutable_borrow_count)
+    }
 
-#[derive(Debug)]
-struct UnsafeRef<'a, T> {
-    index: usize,
-    vec: &'a VecCell<T>,
-}
+    /// Returns the elements as a slice.
+    ///
+    /// Takes `&mut self` so that no element can be mutably borrowed while the slice is in use,
+    /// see [`VecCell::try_as_slice`] for a shared variant.
+    pub fn as_slice(&mut self) -> &[T] {
+        self.elems.get_mut().as_slice()
+    }
 
-#[derive(Debug)]
-pub struct Ref<'a, T>(UnsafeRef<'a, T>);
+    /// Returns the elements as a mutable slice.
+    ///
+    /// No borrow checks are needed, since `&mut self` rules out any live guards.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.elems.get_mut().as_mut_slice()
+    }
 
-#[derive(Debug)]
-pub struct RefMut<'a, T>(UnsafeRef<'a, T>);
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.into_iter()
+    }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Error {
-    OutOfBounds,
-    Aliasing,
+    /// Iterates over the elements in chunks of `chunk_size`, like [`slice::chunks_mut`].
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is zero.
+    #[track_caller]
+    pub fn chunks_mut(&mut self, chunk_size: usize) -> impl Iterator<Item = &mut [T]> {
+        self.as_mut_slice().chunks_mut(chunk_size)
+    }
+
+    /// Runs `f` on every element in index order.
+    pub fn for_each_mut(&mut self, f: impl FnMut(&mut T)) {
+        self.as_mut_slice().iter_mut().for_each(f);
+    }
+
+    /// Runs `f` on every element in index order through a shared reference.
+    ///
+    /// Fails with [`Error::Aliasing`] if any element is borrowed, without calling `f`.
+    /// All elements stay borrowed while `f` runs.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn try_for_each_mut(&self, f: impl FnMut(&mut T)) -> Result<()> {
+        self.try_as_mut_slice()?.iter_mut().for_each(f);
+        Ok(())
+    }
+
+    /// Resolves the source and destination ranges of [`VecCell::copy_within`].
+    fn resolve_within(
+        &self,
+        src: impl RangeBounds<usize>,
+        dest: usize,
+    ) -> Result<(Range<usize>, Range<usize>)> {
+        let src = self.resolve_range(src)?;
+        let end = dest.checked_add(src.len()).ok_or(Error::OutOfBounds)?;
+        let dest = self.resolve_range(dest..end)?;
+        Ok((src, dest))
+    }
+
+    /// Resolves `range` against the length, failing with [`Error::OutOfBounds`] if it doesn't
+    /// fit.
+    fn resolve_range(&self, range: impl RangeBounds<usize>) -> Result<Range<usize>> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1).ok_or(Error::OutOfBounds)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1).ok_or(Error::OutOfBounds)?,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+        if start > end || end > self.len() {
+            return Err(Error::OutOfBounds);
+        }
+        Ok(start..end)
+    }
+
+    fn last_index(&self) -> Result<usize> {
+        self.len().checked_sub(1).ok_or(Error::OutOfBounds)
+    }
+
+    /// Takes borrows leaked on a removed element out of the totals.
+    fn forget_borrows(&self, borrow: &BorrowFlag) {
+        match borrow.get() {
+            BorrowState::None => {}
+            BorrowState::Immutable(count) => remove_borrows(&self.immutable_borrow_count, count),
+            BorrowState::Mutable => remove_borrows(&self.mutable_borrow_count, 1),
+        }
+    }
+
+    #[cold]
+    #[track_caller]
+    fn borrow_failed(&self, index: usize, err: Error) -> ! {
+        match err {
+            Error::OutOfBounds => panic!(
+                "index out of bounds: the len is {} but the index is {index}",
+                self.len()
+            ),
+            Error::Poisoned => panic!("poisoned: index {index}"),
+            _ => {
+                let kind = if self.is_borrowed_mut(index) {
+                    "already mutably borrowed"
+                } else {
+                    "already borrowed"
+                };
+                #[cfg(feature = "debug-borrows")]
+                if let Some(location) = self.borrowed_at(index) {
+                    panic!("{kind}: index {index}, borrowed at {location}");
+                }
+                panic!("{kind}: index {index}");
+            }
+        }
+    }
+
+    /// Borrow flags of all elements.
+    fn borrows(&self) -> &[BorrowFlag] {
+        // # Safety
+        // The `Vec` is only mutated through `&mut self`, or by `VecCell::try_push` and
+        // `VecCell::try_pop`, which only touch unreferenced flags and never reallocate while
+        // any flag is referenced.
+        unsafe { &*self.borrows.get() }
+    }
+
+    /// Pointer to the element at `index`, which must be in bounds.
+    fn elem_ptr(&self, index: usize) -> NonNull<T> {
+        debug_assert!(index < self.len());
+        // # Safety
+        // Only the `Vec` itself is borrowed mutably here, `as_mut_ptr` doesn't create any
+        // references to the elements, which may be borrowed by live guards.
+        // `index` is in bounds, so the offset pointer stays in the allocation.
+        unsafe {
+            let elems = &mut *self.elems.get();
+            NonNull::new_unchecked(elems.as_mut_ptr().add(index))
+        }
+    }
+
+    /// Pointer to the elements in `range`, which must be in bounds.
+    fn slice_ptr(&self, range: Range<usize>) -> NonNull<[T]> {
+        debug_assert!(range.start <= range.end && range.end <= self.len());
+        // # Safety
+        // Same as in `elem_ptr`. `range.start` is at most the length, so the offset pointer
+        // stays in the allocation or one past its end.
+        unsafe {
+            let elems = &mut *self.elems.get();
+            let data = NonNull::new_unchecked(elems.as_mut_ptr().add(range.start));
+            NonNull::slice_from_raw_parts(data, range.len())
+        }
+    }
 }
 
-pub type Result<T> = std::result::Result<T, Error>;
+impl BorrowState {
+    fn add_immutable(self) -> Option<Self> {
+        match self {
+            BorrowState::None => Some(BorrowState::Immutable(1)),
+            BorrowState::Immutable(count) => Some(BorrowState::Immutable(
+                count.checked_add(1).expect("too many immutable borrows"),
+            )),
+            BorrowState::Mutable => None,
+        }
+    }
 
-impl<T> VecCell<T> {
-    pub fn new() -> Self {
-        Self::default()
+    fn remove_immutable(self) -> Self {
+        match self {
+            BorrowState::Immutable(1) => BorrowState::None,
+            BorrowState::Immutable(count) => BorrowState::Immutable(count - 1),
+            // Bookkeeping is corrupted. Keep the state as is, so that at worst the element
+            // stays unavailable instead of being handed out while still referenced.
+            state => {
+                debug_assert!(false, "releasing an immutable borrow in {state:?} state");
+                state
+            }
+        }
     }
 
-    pub fn with_capacity(capacity: usize) -> Self {
+    fn remove_mutable(self) -> Self {
+        match self {
+            BorrowState::Mutable => BorrowState::None,
+            state => {
+                debug_assert!(false, "releasing a mutable borrow in {state:?} state");
+                state
+            }
+        }
+    }
+}
+
+impl BorrowFlag {
+    fn new() -> Self {
         Self {
-            elems: UnsafeCell::new(Vec::with_capacity(capacity)),
-            borrows: Vec::with_capacity(capacity),
-            immutable_borrow_count: Cell::new(0),
-            mutable_borrow_count: Cell::new(0),
+            state: Cell::new(BorrowState::None),
+            shared_mutable: Cell::new(0),
+            #[cfg(feature = "debug-borrows")]
+            borrowed_at: Cell::new(None),
+            #[cfg(feature = "poison")]
+            poisoned: Cell::new(false),
         }
     }
 
-    pub fn len(&self) -> usize {
-        // # Safety
-        // We do not mutate elems here.
-        unsafe { &*self.elems.get() }.len()
+    fn get(&self) -> BorrowState {
+        self.state.get()
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
+    fn set(&self, state: BorrowState) {
+        self.state.set(state);
     }
 
-    pub fn push(&mut self, v: T) {
-        let elems = self.elems.get_mut();
-        elems.push(v);
-        self.borrows.push(Cell::new(BorrowState::None));
+    fn is_poisoned(&self) -> bool {
+        #[cfg(feature = "poison")]
+        return self.poisoned.get();
+        #[cfg(not(feature = "poison"))]
+        false
     }
 
-    pub fn pop(&mut self) -> Option<T> {
-        let elems = self.elems.get_mut();
-        self.borrows.pop();
-        elems.pop()
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    fn record_caller(&self) {
+        #[cfg(feature = "debug-borrows")]
+        self.borrowed_at.set(Some(Location::caller()));
     }
+}
 
-    pub fn get(&self, index: usize) -> Result<Ref<T>> {
-        let borrow = self.borrows.get(index).ok_or(Error::OutOfBounds)?;
-        if borrow.get() == BorrowState::Mutable {
-            Err(Error::Aliasing)
-        } else {
-            borrow.set(BorrowState::Immutable);
-            cell_update(&self.immutable_borrow_count, |c| c + 1);
-            Ok(Ref(UnsafeRef { index, vec: self }))
+impl<'b> BorrowRef<'b> {
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    fn new(borrows: &'b [BorrowFlag], count: &'b Cell<usize>) -> Result<Self> {
+        if !TRACK_BORROWS {
+            return Ok(Self { borrows, count });
+        }
+        if borrows.iter().any(BorrowFlag::is_poisoned) {
+            return Err(Error::Poisoned);
+        }
+        if borrows.iter().any(|b| b.get() == BorrowState::Mutable) {
+            return Err(Error::Aliasing);
         }
+        for borrow in borrows {
+            let state = borrow.get().add_immutable();
+            borrow.set(state.expect("element is not mutably borrowed"));
+            borrow.record_caller();
+        }
+        add_borrows(count, borrows.len());
+        Ok(Self { borrows, count })
     }
 
-    pub fn get_mut(&self, index: usize) -> Result<RefMut<T>> {
-        let borrow = self.borrows.get(index).ok_or(Error::OutOfBounds)?;
-        if borrow.get() != BorrowState::None {
-            Err(Error::Aliasing)
-        } else {
-            borrow.set(BorrowState::Mutable);
-            cell_update(&self.mutable_borrow_count, |c| c + 1);
-            Ok(RefMut(UnsafeRef { index, vec: self }))
+    /// Splits off the borrow of the first element, leaving the rest in `self`.
+    fn split_first(&mut self) -> Option<Self> {
+        let (first, rest) = self.borrows.split_first()?;
+        self.borrows = rest;
+        Some(Self {
+            borrows: core::slice::from_ref(first),
+            count: self.count,
+        })
+    }
+
+    /// Splits off the borrow of the last element, leaving the rest in `self`.
+    fn split_last(&mut self) -> Option<Self> {
+        let (last, rest) = self.borrows.split_last()?;
+        self.borrows = rest;
+        Some(Self {
+            borrows: core::slice::from_ref(last),
+            count: self.count,
+        })
+    }
+}
+
+impl Clone for BorrowRef<'_> {
+    fn clone(&self) -> Self {
+        if TRACK_BORROWS {
+            for borrow in self.borrows {
+                let state = borrow.get().add_immutable();
+                borrow.set(state.expect("element is immutably borrowed"));
+            }
+            add_borrows(self.count, self.borrows.len());
+        }
+        Self {
+            borrows: self.borrows,
+            count: self.count,
+        }
+    }
+}
+
+impl Drop for BorrowRef<'_> {
+    fn drop(&mut self) {
+        if !TRACK_BORROWS {
+            return;
         }
+        for borrow in self.borrows {
+            borrow.set(borrow.get().remove_immutable());
+        }
+        remove_borrows(self.count, self.borrows.len());
     }
+}
 
-    pub fn try_iter(&self) -> Result<impl Iterator<Item = &T>> {
-        if self.mutable_borrow_count.get() != 0 {
+impl<'b> BorrowRefMut<'b> {
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    fn new(borrows: &'b [BorrowFlag], count: &'b Cell<usize>) -> Result<Self> {
+        // Only built once the borrow is taken, dropping it releases the borrow.
+        let guard = || Self {
+            borrows,
+            count,
+            #[cfg(feature = "poison")]
+            poison: !std::thread::panicking(),
+        };
+        if !TRACK_BORROWS {
+            return Ok(guard());
+        }
+        if borrows.iter().any(BorrowFlag::is_poisoned) {
+            return Err(Error::Poisoned);
+        }
+        if borrows.iter().any(|b| b.get() != BorrowState::None) {
             return Err(Error::Aliasing);
         }
-        // # Safety
-        // Asserted above that no element is mutably borrowed.
-        // Aliasing rules allow multiple immutable borrows.
-        let elems = unsafe { &*self.elems.get() };
-        Ok(elems.iter())
+        for borrow in borrows {
+            borrow.set(BorrowState::Mutable);
+            borrow.record_caller();
+        }
+        add_borrows(count, borrows.len());
+        Ok(guard())
     }
 
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
-        self.into_iter()
+    /// Splits off the borrow of the first element, leaving the rest in `self`.
+    fn split_first(&mut self) -> Option<Self> {
+        let (first, rest) = self.borrows.split_first()?;
+        self.borrows = rest;
+        Some(Self {
+            borrows: core::slice::from_ref(first),
+            count: self.count,
+            #[cfg(feature = "poison")]
+            poison: self.poison,
+        })
+    }
+
+    /// Splits off the borrow of the last element, leaving the rest in `self`.
+    fn split_last(&mut self) -> Option<Self> {
+        let (last, rest) = self.borrows.split_last()?;
+        self.borrows = rest;
+        Some(Self {
+            borrows: core::slice::from_ref(last),
+            count: self.count,
+            #[cfg(feature = "poison")]
+            poison: self.poison,
+        })
+    }
+
+    /// Another guard of the same borrow. The elements are released once all guards are dropped.
+    fn share(&self) -> Self {
+        if TRACK_BORROWS {
+            for borrow in self.borrows {
+                cell_update(&borrow.shared_mutable, |n| {
+                    n.checked_add(1).expect("too many mutable borrows")
+                });
+            }
+        }
+        Self {
+            borrows: self.borrows,
+            count: self.count,
+            #[cfg(feature = "poison")]
+            poison: self.poison,
+        }
+    }
+}
+
+impl Drop for BorrowRefMut<'_> {
+    fn drop(&mut self) {
+        if !TRACK_BORROWS {
+            return;
+        }
+        let mut released = 0;
+        for borrow in self.borrows {
+            if borrow.shared_mutable.get() > 0 {
+                cell_update(&borrow.shared_mutable, |n| n - 1);
+            } else {
+                borrow.set(borrow.get().remove_mutable());
+                released += 1;
+            }
+            #[cfg(feature = "poison")]
+            if self.poison && std::thread::panicking() {
+                borrow.poisoned.set(true);
+            }
+        }
+        remove_borrows(self.count, released);
     }
 }
 
@@ -117,38 +1801,131 @@ impl<T> Default for VecCell<T> {
     fn default() -> Self {
         Self {
             elems: UnsafeCell::new(Vec::new()),
-            borrows: Vec::new(),
+            borrows: UnsafeCell::new(Vec::new()),
             immutable_borrow_count: Cell::new(0),
             mutable_borrow_count: Cell::new(0),
         }
     }
 }
 
+impl<T: Clone> Clone for VecCell<T> {
+    /// Clones all elements into a new `VecCell` without any borrows.
+    ///
+    /// # Panics
+    /// Panics if any element is mutably borrowed, like [`RefCell`](core::cell::RefCell) does.
+    /// For a non-panicking variant use [`VecCell::try_clone`].
+    #[track_caller]
+    fn clone(&self) -> Self {
+        match self.try_clone() {
+            Ok(v) => v,
+            Err(err) => panic!("cannot clone VecCell: {err}"),
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for VecCell<T> {
+    /// Compares the elements, ignoring their borrow states.
+    ///
+    /// # Panics
+    /// Panics if any element of either `VecCell` is mutably borrowed, like
+    /// [`RefCell`](core::cell::RefCell) does.
+    #[track_caller]
+    fn eq(&self, other: &Self) -> bool {
+        match (self.try_iter(), other.try_iter()) {
+            (Ok(a), Ok(b)) => a.eq(b),
+            (Err(err), _) | (_, Err(err)) => panic!("cannot compare VecCell: {err}"),
+        }
+    }
+}
+
+impl<T: Eq> Eq for VecCell<T> {}
+
+impl<T: Hash> Hash for VecCell<T> {
+    /// Hashes the elements like a `Vec` of them would be, ignoring their borrow states.
+    ///
+    /// # Panics
+    /// Panics if any element is mutably borrowed.
+    #[track_caller]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self.try_iter() {
+            Ok(iter) => iter.iter.as_slice().hash(state),
+            Err(err) => panic!("cannot hash VecCell: {err}"),
+        }
+    }
+}
+
 impl<T> FromIterator<T> for VecCell<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let elems: Vec<_> = iter.into_iter().collect();
-        let len = elems.len();
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<T> From<Vec<T>> for VecCell<T> {
+    fn from(elems: Vec<T>) -> Self {
+        // Room for as many flags as elements, so that `try_push` can use all of the capacity.
+        let mut borrows = Vec::with_capacity(elems.capacity());
+        borrows.resize(elems.len(), BorrowFlag::new());
         Self {
             elems: UnsafeCell::new(elems),
-            borrows: vec![Cell::new(BorrowState::None); len],
+            borrows: UnsafeCell::new(borrows),
             immutable_borrow_count: Cell::new(0),
             mutable_borrow_count: Cell::new(0),
         }
     }
 }
 
+impl<T, const N: usize> From<[T; N]> for VecCell<T> {
+    fn from(elems: [T; N]) -> Self {
+        Self::from(Vec::from(elems))
+    }
+}
+
+impl<T> From<VecCell<T>> for Vec<T> {
+    fn from(v: VecCell<T>) -> Self {
+        v.into_vec()
+    }
+}
+
+impl<T> Extend<T> for VecCell<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for v in iter {
+            self.push(v);
+        }
+    }
+}
+
 impl<T> IntoIterator for VecCell<T> {
     type Item = T;
     type IntoIter = <Vec<T> as IntoIterator>::IntoIter;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.elems.into_inner().into_iter()
+        self.into_vec().into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a VecCell<T> {
+    type Item = Ref<'a, T>;
+    type IntoIter = IterRefs<'a, T>;
+
+    /// Immutably borrows every element, see [`VecCell::iter_refs`].
+    ///
+    /// # Panics
+    /// Panics if any element is mutably borrowed, like indexing does, since `into_iter` can't
+    /// return an error. For a non-panicking variant use [`VecCell::iter_refs`].
+    #[track_caller]
+    fn into_iter(self) -> Self::IntoIter {
+        match self.iter_refs() {
+            Ok(iter) => iter,
+            Err(err) => panic!("cannot iterate VecCell: {err}"),
+        }
     }
 }
 
 impl<'a, T> IntoIterator for &'a mut VecCell<T> {
     type Item = &'a mut T;
-    type IntoIter = std::slice::IterMut<'a, T>;
+    type IntoIter = core::slice::IterMut<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
         let elems = unsafe { &mut *self.elems.get() };
@@ -156,102 +1933,347 @@ impl<'a, T> IntoIterator for &'a mut VecCell<T> {
     }
 }
 
-impl<'a, T> UnsafeRef<'a, T> {
-    /// # Safety
-    /// Need to ensure we do not create aliasing mutable borrow and that `self.index` is in
-    /// `self.vec`'s bounds.
-    unsafe fn deref(&self) -> &T {
-        unsafe {
-            let elems = &*self.vec.elems.get();
-            elems.get_unchecked(self.index)
+impl<'b, T: ?Sized> Ref<'b, T> {
+    /// Makes another `Ref` to the same element, like [`core::cell::Ref::clone`].
+    ///
+    /// The element stays immutably borrowed until all clones are dropped. This is an associated
+    /// function, so that `r.clone()` still clones the element.
+    #[allow(clippy::should_implement_trait)]
+    pub fn clone(orig: &Ref<'b, T>) -> Ref<'b, T> {
+        Ref {
+            value: orig.value,
+            _borrow: orig._borrow.clone(),
         }
     }
 
-    /// # Safety
-    /// Need to ensure we do not create aliasing borrow of any kind  and that self.index is in
-    /// `self.vec`'s bounds.
-    unsafe fn deref_mut(&mut self) -> &mut T {
-        unsafe {
-            let elems = &mut *self.vec.elems.get();
-            elems.get_unchecked_mut(self.index)
+    /// Makes a new `Ref` for a component of the borrowed element, like [`core::cell::Ref::map`].
+    ///
+    /// The element stays immutably borrowed until the returned `Ref` is dropped.
+    pub fn map<U: ?Sized>(orig: Ref<'b, T>, f: impl FnOnce(&T) -> &U) -> Ref<'b, U> {
+        let value = NonNull::from(f(&*orig));
+        Ref {
+            value,
+            _borrow: orig._borrow,
         }
     }
 }
 
-impl<T> Deref for Ref<'_, T> {
+impl<T: ?Sized> Deref for Ref<'_, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
         // # Safety
-        // Preconditions ensured on `Ref`'s construction in [`VecCell::get`].
+        // `value` points to an element that stays immutably borrowed while `self` lives.
         // `VecCell` makes sure that we never break invariants.
-        unsafe { self.0.deref() }
+        unsafe { self.value.as_ref() }
     }
 }
 
-impl<T> Drop for Ref<'_, T> {
-    fn drop(&mut self) {
-        // # Safety
-        // We assert on Ref construction that index is in bounds.
-        let borrow = unsafe { self.0.vec.borrows.get_unchecked(self.0.index) };
-        let new_count = cell_update(&self.0.vec.immutable_borrow_count, |count| count - 1);
-        if new_count == 0 {
-            borrow.set(BorrowState::None);
-        }
+impl<T: ?Sized + Display> Display for Ref<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", &**self)
     }
 }
 
-impl<T: Display> Display for Ref<'_, T> {
+impl<T: ?Sized + Debug> Debug for Ref<'_, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", **self)
+        (**self).fmt(f)
+    }
+}
+
+impl<'b, T: ?Sized> RefMut<'b, T> {
+    /// Makes a new `RefMut` for a component of the borrowed element, like
+    /// [`core::cell::RefMut::map`].
+    ///
+    /// The element stays mutably borrowed until the returned `RefMut` is dropped.
+    pub fn map<U: ?Sized>(
+        mut orig: RefMut<'b, T>,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> RefMut<'b, U> {
+        let value = NonNull::from(f(&mut *orig));
+        RefMut {
+            value,
+            _borrow: orig._borrow,
+            marker: PhantomData,
+        }
+    }
+
+    /// Splits a `RefMut` into guards of two disjoint components of the borrowed element, like
+    /// [`core::cell::RefMut::map_split`].
+    ///
+    /// The element stays mutably borrowed until both returned guards are dropped.
+    pub fn map_split<U: ?Sized, V: ?Sized>(
+        mut orig: RefMut<'b, T>,
+        f: impl FnOnce(&mut T) -> (&mut U, &mut V),
+    ) -> (RefMut<'b, U>, RefMut<'b, V>) {
+        let (a, b) = f(&mut *orig);
+        let (a, b) = (NonNull::from(a), NonNull::from(b));
+        let borrow = orig._borrow.share();
+        let a = RefMut {
+            value: a,
+            _borrow: orig._borrow,
+            marker: PhantomData,
+        };
+        let b = RefMut {
+            value: b,
+            _borrow: borrow,
+            marker: PhantomData,
+        };
+        (a, b)
     }
 }
 
-impl<T> Deref for RefMut<'_, T> {
+impl<T: ?Sized> Deref for RefMut<'_, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        // Preconditions ensured on `RefMut`'s construction in [`VecCell::get_mut`]
+        // # Safety
+        // `value` points to an element that stays mutably borrowed while `self` lives.
         // `VecCell` makes sure that we never break invariants.
-        unsafe { self.0.deref() }
+        unsafe { self.value.as_ref() }
     }
 }
 
-impl<T> DerefMut for RefMut<'_, T> {
+impl<T: ?Sized> DerefMut for RefMut<'_, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        // Preconditions ensured on `RefMut`'s construction in [`VecCell::get_mut`]
+        // # Safety
+        // `value` points to an element that stays mutably borrowed while `self` lives.
         // `VecCell` makes sure that we never break invariants.
-        unsafe { self.0.deref_mut() }
+        unsafe { self.value.as_mut() }
     }
 }
 
-impl<T> Drop for RefMut<'_, T> {
-    fn drop(&mut self) {
-        // # Safety
-        // We assert on Ref construction that index is in bounds.
-        let borrow = unsafe { self.0.vec.borrows.get_unchecked(self.0.index) };
-        cell_update(&self.0.vec.mutable_borrow_count, |count| count - 1);
-        borrow.set(BorrowState::None);
+impl<T: ?Sized + Display> Display for RefMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", &**self)
     }
 }
 
-impl<T: Display> Display for RefMut<'_, T> {
+impl<T: ?Sized + Debug> Debug for RefMut<'_, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", **self)
+        (**self).fmt(f)
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> Iterator for IterRefs<'a, T> {
+    type Item = Ref<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.iter.next()?;
+        let borrow = self.borrow.split_first().expect("one borrow per element");
+        Some(Ref {
+            value: NonNull::from(value),
+            _borrow: borrow,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IterRefs<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let value = self.iter.next_back()?;
+        let borrow = self.borrow.split_last().expect("one borrow per element");
+        Some(Ref {
+            value: NonNull::from(value),
+            _borrow: borrow,
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterRefs<'_, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = RefMut<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.iter.next()?;
+        let borrow = self.borrow.split_first().expect("one borrow per element");
+        Some(RefMut {
+            value: NonNull::from(value),
+            _borrow: borrow,
+            marker: PhantomData,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let value = self.iter.next_back()?;
+        let borrow = self.borrow.split_last().expect("one borrow per element");
+        Some(RefMut {
+            value: NonNull::from(value),
+            _borrow: borrow,
+            marker: PhantomData,
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+#[cfg(feature = "poison")]
+impl<T> Drop for IterMut<'_, T> {
+    fn drop(&mut self) {
+        // Elements never yielded can't have been left half-updated by a panic.
+        self.borrow.poison = false;
+    }
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Drain<'_, T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.iter.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+impl<T> Drop for Drain<'_, T> {
+    fn drop(&mut self) {
+        // The elements after the range are moved back when `iter` is dropped right after this,
+        // with no user code able to reach the `VecCell` in between.
+        let len = self.borrows.len() + self.tail_len;
+        self.borrows.resize(len, BorrowFlag::new());
+    }
+}
+
+impl private::Sealed for usize {}
+
+impl<T> VecCellIndex<T> for usize {
+    type Output = T;
+
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    fn get(self, cell: &VecCell<T>) -> Result<Ref<'_, T>> {
+        let borrows = cell.borrows().get(self..=self).ok_or(Error::OutOfBounds)?;
+        let borrow = BorrowRef::new(borrows, &cell.immutable_borrow_count)?;
+        Ok(Ref {
+            value: cell.elem_ptr(self),
+            _borrow: borrow,
+        })
+    }
+
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    fn get_mut(self, cell: &VecCell<T>) -> Result<RefMut<'_, T>> {
+        let borrows = cell.borrows().get(self..=self).ok_or(Error::OutOfBounds)?;
+        let borrow = BorrowRefMut::new(borrows, &cell.mutable_borrow_count)?;
+        Ok(RefMut {
+            value: cell.elem_ptr(self),
+            _borrow: borrow,
+            marker: PhantomData,
+        })
     }
 }
 
+macro_rules! impl_range_index {
+    ($($range:ty),*) => {$(
+        impl private::Sealed for $range {}
+
+        impl<T> VecCellIndex<T> for $range {
+            type Output = [T];
+
+            #[cfg_attr(feature = "debug-borrows", track_caller)]
+            fn get(self, cell: &VecCell<T>) -> Result<Ref<'_, [T]>> {
+                cell.get_range(self)
+            }
+
+            #[cfg_attr(feature = "debug-borrows", track_caller)]
+            fn get_mut(self, cell: &VecCell<T>) -> Result<RefMut<'_, [T]>> {
+                cell.get_range_mut(self)
+            }
+        }
+    )*};
+}
+
+impl_range_index!(
+    Range<usize>,
+    RangeInclusive<usize>,
+    RangeFrom<usize>,
+    RangeTo<usize>,
+    RangeToInclusive<usize>,
+    RangeFull
+);
+
 impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Error::OutOfBounds => write!(f, "Out of bounds index"),
             Error::Aliasing => write!(f, "Borrow would lead to illegal aliasing"),
+            Error::DuplicateIndex => write!(f, "Same index borrowed mutably more than once"),
+            Error::Poisoned => write!(f, "Element was poisoned by a panic while mutably borrowed"),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {}
 
+// Fails to compile if the guards stop being covariant where they should be. Invariance of
+// `RefMut` over `T` is checked by the `compile_fail` examples on it.
+const _: () = {
+    fn covariant<'a>(
+        r: Ref<'static, &'static str>,
+        m: RefMut<'static, ()>,
+    ) -> (Ref<'a, &'a str>, RefMut<'a, ()>) {
+        (r, m)
+    }
+    let _ = covariant;
+};
+
+/// Whether two ranges share an element. Empty ranges contain no elements to alias.
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    !a.is_empty() && !b.is_empty() && a.start < b.end && b.start < a.end
+}
+
+fn add_borrows(count: &Cell<usize>, n: usize) {
+    cell_update(count, |c| {
+        c.checked_add(n).expect("borrow counter overflow")
+    });
+}
+
+fn remove_borrows(count: &Cell<usize>, n: usize) {
+    cell_update(count, |c| {
+        debug_assert!(c >= n, "borrow counter underflow");
+        c.saturating_sub(n)
+    });
+}
+
 fn cell_update<T: Copy>(cell: &Cell<T>, f: impl FnOnce(T) -> T) -> T {
     let v = cell.get();
     let new = f(v);
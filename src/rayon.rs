@@ -0,0 +1,94 @@
+use core::mem::ManuallyDrop;
+use std::thread::{self, ThreadId};
+
+use ::rayon::{
+    iter::{
+        plumbing::{Consumer, ProducerCallback, UnindexedConsumer},
+        IndexedParallelIterator, IntoParallelIterator, ParallelIterator,
+    },
+    slice::{Iter, IterMut},
+};
+
+use crate::{BorrowRef, Result, VecCell};
+
+/// Parallel iterator returned by [`VecCell::par_try_iter`].
+///
+/// Every element stays immutably borrowed until the iterator is dropped. The borrow counters
+/// aren't thread-safe, so they are only updated on the thread that created the iterator.
+/// Dropping it on another thread leaks the borrow, which can then only be released by
+/// [`VecCell::reset_borrows`].
+pub struct ParIter<'a, T> {
+    iter: Iter<'a, T>,
+    borrow: ManuallyDrop<BorrowRef<'a>>,
+    owner: ThreadId,
+}
+
+// # Safety
+// Only `&T`s are shared with other threads, which `T: Sync` allows. The borrow is only
+// released on the thread that took it, see `ParIter::drop`.
+unsafe impl<T: Sync> Send for ParIter<'_, T> {}
+
+impl<T: Sync> VecCell<T> {
+    /// Immutably borrows every element, iterating over them in parallel.
+    ///
+    /// Fails with [`Error::Aliasing`](crate::Error::Aliasing) if any element is mutably
+    /// borrowed.
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn par_try_iter(&self) -> Result<ParIter<'_, T>> {
+        let borrow = self.borrow_all()?;
+        // # Safety
+        // Every element is now immutably borrowed until `ParIter` is dropped.
+        let elems = unsafe { &*self.elems.get() };
+        Ok(ParIter {
+            iter: elems[..self.tracked_len()].into_par_iter(),
+            borrow: ManuallyDrop::new(borrow),
+            owner: thread::current().id(),
+        })
+    }
+}
+
+impl<'a, T: Send> IntoParallelIterator for &'a mut VecCell<T> {
+    type Iter = IterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.as_mut_slice().into_par_iter()
+    }
+}
+
+impl<'a, T: Sync> ParallelIterator for ParIter<'a, T> {
+    type Item = &'a T;
+
+    fn drive_unindexed<C: UnindexedConsumer<Self::Item>>(self, consumer: C) -> C::Result {
+        // `self` is dropped once driving is done, releasing the borrow.
+        self.iter.clone().drive_unindexed(consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+impl<T: Sync> IndexedParallelIterator for ParIter<'_, T> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+
+    fn drive<C: Consumer<Self::Item>>(self, consumer: C) -> C::Result {
+        self.iter.clone().drive(consumer)
+    }
+
+    fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
+        self.iter.clone().with_producer(callback)
+    }
+}
+
+impl<T> Drop for ParIter<'_, T> {
+    fn drop(&mut self) {
+        if thread::current().id() == self.owner {
+            // # Safety
+            // The borrow is dropped only once, here.
+            unsafe { ManuallyDrop::drop(&mut self.borrow) };
+        }
+    }
+}
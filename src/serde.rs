@@ -0,0 +1,23 @@
+use alloc::{format, vec::Vec};
+
+use ::serde::{ser::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::VecCell;
+
+/// Serializes the elements as a sequence, like `Vec<T>`. Fails if any element is mutably
+/// borrowed.
+impl<T: Serialize> Serialize for VecCell<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let iter = self
+            .try_iter()
+            .map_err(|err| S::Error::custom(format!("cannot serialize VecCell: {err}")))?;
+        serializer.collect_seq(iter)
+    }
+}
+
+/// Deserializes a sequence, like `Vec<T>`, into a `VecCell` with no element borrowed.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for VecCell<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(Self::from)
+    }
+}
@@ -0,0 +1,184 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    cell::{Cell, UnsafeCell},
+    fmt::{self, Debug},
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ptr::NonNull,
+    slice,
+};
+
+use crate::{BorrowFlag, BorrowRef, BorrowRefMut, BorrowState, Error, Ref, RefMut, Result};
+
+/// Number of elements in each chunk of a [`StableVecCell`].
+const CHUNK_LEN: usize = 64;
+
+/// A [`VecCell`](crate::VecCell) whose elements never move, so it can grow through `&self`
+/// while elements are borrowed.
+///
+/// Elements live in fixed-size chunks that are allocated as needed and never reallocated.
+/// Borrows are tracked per element exactly like in `VecCell`, but since the elements aren't
+/// contiguous there are no slice borrows.
+pub struct StableVecCell<T> {
+    // Raw pointers instead of `Box`es, as moving a `Box` would assert unique access to the
+    // chunk while guards point into it.
+    chunks: UnsafeCell<Vec<NonNull<Slot<T>>>>,
+    len: Cell<usize>,
+    immutable_borrow_count: Cell<usize>,
+    mutable_borrow_count: Cell<usize>,
+    marker: PhantomData<T>,
+}
+
+struct Slot<T> {
+    borrow: BorrowFlag,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+// # Safety
+// The chunks are owned like the elements of a `Vec<T>`.
+unsafe impl<T: Send> Send for StableVecCell<T> {}
+
+impl<T> StableVecCell<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends `v` through a shared reference. Never moves the other elements, so it works
+    /// while any of them are borrowed.
+    pub fn push(&self, v: T) {
+        let index = self.len();
+        // # Safety
+        // Only the `Vec` of chunk pointers is mutated, guards point into the chunks themselves.
+        let chunks = unsafe { &mut *self.chunks.get() };
+        if index / CHUNK_LEN == chunks.len() {
+            let chunk: Box<[Slot<T>]> = (0..CHUNK_LEN)
+                .map(|_| Slot {
+                    borrow: BorrowFlag::new(),
+                    value: UnsafeCell::new(MaybeUninit::uninit()),
+                })
+                .collect();
+            let chunk = NonNull::new(Box::into_raw(chunk).cast()).expect("Box is non-null");
+            chunks.push(chunk);
+        }
+        let slot = self.slot_ptr(index);
+        // # Safety
+        // The slot is past the length, so it is uninitialized and no guard refers to it.
+        unsafe { (*slot.as_ref().value.get()).write(v) };
+        self.len.set(index + 1);
+    }
+
+    pub fn borrow_state(&self, index: usize) -> Result<BorrowState> {
+        Ok(self.slot(index)?.borrow.get())
+    }
+
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn get(&self, index: usize) -> Result<Ref<'_, T>> {
+        let slot = self.slot(index)?;
+        let borrow = BorrowRef::new(slice::from_ref(&slot.borrow), &self.immutable_borrow_count)?;
+        Ok(Ref {
+            value: NonNull::from(&slot.value).cast(),
+            _borrow: borrow,
+        })
+    }
+
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
+    pub fn get_mut(&self, index: usize) -> Result<RefMut<'_, T>> {
+        let slot = self.slot(index)?;
+        let borrow = BorrowRefMut::new(slice::from_ref(&slot.borrow), &self.mutable_borrow_count)?;
+        Ok(RefMut {
+            value: NonNull::from(&slot.value).cast(),
+            _borrow: borrow,
+            marker: PhantomData,
+        })
+    }
+
+    /// Consumes the `StableVecCell`, moving the elements into a `Vec`.
+    pub fn into_vec(self) -> Vec<T> {
+        let mut elems = Vec::with_capacity(self.len());
+        for index in 0..self.len() {
+            // # Safety
+            // The element is initialized, and the length is reset below so that it isn't
+            // dropped again.
+            elems.push(unsafe { (*self.slot_ptr(index).as_ref().value.get()).assume_init_read() });
+        }
+        self.len.set(0);
+        elems
+    }
+
+    fn slot(&self, index: usize) -> Result<&Slot<T>> {
+        if index >= self.len() {
+            return Err(Error::OutOfBounds);
+        }
+        // # Safety
+        // Slots below the length are initialized and live as long as `self`.
+        Ok(unsafe { self.slot_ptr(index).as_ref() })
+    }
+
+    /// Pointer to the slot at `index`, whose chunk must be allocated.
+    fn slot_ptr(&self, index: usize) -> NonNull<Slot<T>> {
+        // # Safety
+        // We do not mutate chunks here. The offset is within the chunk's `CHUNK_LEN` slots.
+        unsafe {
+            let chunks = &*self.chunks.get();
+            chunks[index / CHUNK_LEN].add(index % CHUNK_LEN)
+        }
+    }
+}
+
+impl<T> Default for StableVecCell<T> {
+    fn default() -> Self {
+        Self {
+            chunks: UnsafeCell::new(Vec::new()),
+            len: Cell::new(0),
+            immutable_borrow_count: Cell::new(0),
+            mutable_borrow_count: Cell::new(0),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for StableVecCell<T> {
+    fn drop(&mut self) {
+        for index in 0..self.len() {
+            // # Safety
+            // The element is initialized and `&mut self` proves it isn't borrowed.
+            unsafe { (*self.slot_ptr(index).as_ref().value.get()).assume_init_drop() };
+        }
+        for &chunk in self.chunks.get_mut().iter() {
+            // # Safety
+            // Every chunk was allocated in `push` as a boxed slice of `CHUNK_LEN` slots.
+            drop(unsafe {
+                Box::from_raw(core::ptr::slice_from_raw_parts_mut(
+                    chunk.as_ptr(),
+                    CHUNK_LEN,
+                ))
+            });
+        }
+    }
+}
+
+impl<T> FromIterator<T> for StableVecCell<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let v = Self::new();
+        for elem in iter {
+            v.push(elem);
+        }
+        v
+    }
+}
+
+impl<T> Debug for StableVecCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StableVecCell")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}